@@ -0,0 +1,94 @@
+//! Compares the fit strategies of [`Heap`] on a few synthetic workloads.
+//!
+//! Run with `cargo bench --bench fit_strategies`. For each strategy and workload, this
+//! reports the time per operation, and how many requests were refused because the heap
+//! was too fragmented to satisfy them.
+
+use std::{any, hint::black_box, ptr::NonNull, time::Instant};
+
+use divvy::{
+    Allocator, BestFit, Deallocator, FirstFit, FitStrategy, Heap, NextFit, NonZeroLayout,
+    SegregatedFit,
+};
+
+const HEAP_SIZE: usize = 1 << 24;
+const OPERATIONS: usize = 1_000_000;
+const MAX_LIVE: usize = 4096;
+
+struct Workload {
+    name: &'static str,
+    sizes: fn(&mut Rng) -> usize,
+}
+
+const WORKLOADS: &[Workload] = &[
+    Workload {
+        name: "small",
+        sizes: |rng| 8 + rng.below(120),
+    },
+    Workload {
+        name: "mixed",
+        sizes: |rng| 1 << (3 + rng.below(10)),
+    },
+    Workload {
+        name: "large",
+        sizes: |rng| 1024 + rng.below(16 * 1024),
+    },
+];
+
+fn main() {
+    for workload in WORKLOADS {
+        bench(workload, FirstFit);
+        bench(workload, NextFit::new());
+        bench(workload, BestFit);
+        bench(workload, SegregatedFit::new());
+    }
+}
+
+fn bench<S: FitStrategy>(workload: &Workload, strategy: S) {
+    let mut buffer = vec![0u8; HEAP_SIZE];
+    let heap = Heap::with_strategy(strategy);
+    unsafe { heap.init(buffer.as_mut_ptr(), buffer.len()) };
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut live: Vec<(NonNull<u8>, NonZeroLayout)> = Vec::with_capacity(MAX_LIVE);
+    let mut failures = 0;
+
+    let start = Instant::now();
+    for _ in 0..OPERATIONS {
+        if live.len() < MAX_LIVE && (live.is_empty() || rng.below(2) == 0) {
+            let layout = NonZeroLayout::from_size_align((workload.sizes)(&mut rng), 8).unwrap();
+            match heap.allocate(layout) {
+                Ok(ptr) => live.push((black_box(ptr), layout)),
+                Err(_) => failures += 1,
+            }
+        } else {
+            let (ptr, layout) = live.swap_remove(rng.below(live.len()));
+            unsafe { heap.deallocate(ptr, layout) };
+        }
+    }
+    let elapsed = start.elapsed();
+
+    for (ptr, layout) in live {
+        unsafe { heap.deallocate(ptr, layout) };
+    }
+
+    println!(
+        "{:<6} {:<14} {:>8.1} ns/op {:>8} refused",
+        workload.name,
+        any::type_name::<S>().rsplit("::").next().unwrap(),
+        elapsed.as_nanos() as f64 / OPERATIONS as f64,
+        failures,
+    );
+}
+
+/// A small xorshift generator, so that every strategy sees the same requests.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
@@ -0,0 +1,154 @@
+use core::{
+    alloc::Layout,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, NonZeroLayout};
+
+/// A box whose memory comes from a borrowed allocator, and which stores nothing but the
+/// pointer to its value.
+///
+/// Dropping a `BorrowedBox` runs the value's destructor but does not deallocate its
+/// memory, which is left for the allocator to reclaim in bulk, as arenas and bump
+/// allocators do when they are reset or dropped. The box borrows the allocator for its
+/// whole lifetime, so that the memory cannot be reclaimed while the value is still in
+/// use.
+///
+/// With any other allocator, the memory of every `BorrowedBox` is leaked. Use
+/// [`Box`](crate::boxed::Box) there instead.
+pub struct BorrowedBox<'a, T>
+where
+    T: ?Sized,
+{
+    ptr: NonNull<T>,
+    _p: PhantomData<(&'a (), T)>,
+}
+
+unsafe impl<T> Send for BorrowedBox<'_, T> where T: ?Sized + Send {}
+unsafe impl<T> Sync for BorrowedBox<'_, T> where T: ?Sized + Sync {}
+
+impl<'a, T> BorrowedBox<'a, T> {
+    #[inline]
+    pub fn try_new_in<A>(value: T, allocator: &'a A) -> Result<Self, AllocError>
+    where
+        A: Allocator + ?Sized,
+    {
+        let ptr = match NonZeroLayout::new(Layout::new::<T>()) {
+            Some(layout) => allocator.allocate(layout)?.cast::<T>(),
+            None => NonNull::dangling(),
+        };
+        unsafe { ptr.as_ptr().write(value) };
+        Ok(Self {
+            ptr,
+            _p: PhantomData,
+        })
+    }
+
+    #[inline]
+    pub fn new_in<A>(value: T, allocator: &'a A) -> Self
+    where
+        A: Allocator + ?Sized,
+    {
+        Self::try_new_in(value, allocator).expect("allocation failed")
+    }
+
+    /// Move the value out of the box. Its memory is left to the allocator.
+    pub fn into_inner(b: BorrowedBox<'a, T>) -> T {
+        let b = ManuallyDrop::new(b);
+        unsafe { b.ptr.as_ptr().read() }
+    }
+}
+
+impl<'a, T> BorrowedBox<'a, T>
+where
+    T: ?Sized,
+{
+    /// # Safety
+    ///
+    /// The pointer must refer to a valid, initialized value that is owned by the caller
+    /// and remains allocated for `'a`.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            _p: PhantomData,
+        }
+    }
+
+    /// Release ownership of the value without dropping it.
+    #[inline]
+    pub fn into_raw(b: BorrowedBox<'a, T>) -> *mut T {
+        ManuallyDrop::new(b).ptr.as_ptr()
+    }
+
+    pub fn leak(b: BorrowedBox<'a, T>) -> &'a mut T {
+        let mut b = ManuallyDrop::new(b);
+        unsafe { b.ptr.as_mut() }
+    }
+}
+
+impl<T> Deref for BorrowedBox<'_, T>
+where
+    T: ?Sized,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for BorrowedBox<'_, T>
+where
+    T: ?Sized,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> Drop for BorrowedBox<'_, T>
+where
+    T: ?Sized,
+{
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.ptr.as_ptr()) };
+    }
+}
+
+impl<T> Debug for BorrowedBox<'_, T>
+where
+    T: ?Sized + Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Debug::fmt(v, f)
+    }
+}
+
+impl<T> Display for BorrowedBox<'_, T>
+where
+    T: ?Sized + Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Display::fmt(v, f)
+    }
+}
+
+impl<T> Hash for BorrowedBox<'_, T>
+where
+    T: ?Sized + Hash,
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
@@ -47,6 +47,15 @@ where
         unsafe { Ok(Pin::new_unchecked(b)) }
     }
 
+    /// Clone the value into a new box, allocated from a clone of this box's allocator.
+    pub fn try_clone(b: &Box<T, A>) -> Result<Box<T, A>, AllocError>
+    where
+        T: Clone,
+        A: Clone,
+    {
+        Box::try_new_in(b.deref().clone(), b.allocator.clone())
+    }
+
     #[inline]
     pub fn new_uninit_in(allocator: A) -> Box<MaybeUninit<T>, A> {
         Box::try_new_uninit_in(allocator).expect("allocation failed")
@@ -74,6 +83,10 @@ where
     }
 
     pub fn into_inner(b: Box<T, A>) -> T {
+        Box::into_inner_with_allocator(b).0
+    }
+
+    pub fn into_inner_with_allocator(b: Box<T, A>) -> (T, A) {
         let (ptr, allocator) = Box::into_raw_with_allocator(b);
 
         let layout = Layout::new::<T>();
@@ -81,7 +94,7 @@ where
         if let Some(layout) = NonZeroLayout::new(layout) {
             unsafe { allocator.deallocate(NonNull::new_unchecked(ptr).cast(), layout) };
         }
-        value
+        (value, allocator)
     }
 }
 
@@ -166,9 +179,12 @@ where
     A: Deallocator,
 {
     fn drop(&mut self) {
-        let layout = Layout::for_value(self.deref());
-        if let Some(layout) = NonZeroLayout::new(layout) {
-            unsafe { self.allocator.deallocate(self.ptr.cast(), layout) };
+        let layout = Layout::for_value::<T>(self);
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            if let Some(layout) = NonZeroLayout::new(layout) {
+                self.allocator.deallocate(self.ptr.cast(), layout);
+            }
         }
     }
 }
@@ -211,12 +227,22 @@ where
 
 impl<T, A> Clone for Box<T, A>
 where
-    T: ?Sized + Clone,
+    T: Clone,
     A: Allocator + Clone,
 {
     fn clone(&self) -> Self {
-        let value = self.deref().clone();
-        let allocator = self.allocator.clone();
-        Box::new_in(value, allocator)
+        Box::try_clone(self).expect("allocation failed")
     }
 }
+
+/// Move a value into a [`Box`] from an allocator.
+///
+/// ```ignore
+/// let node = boxed_in!(&arena, Node::new());
+/// ```
+#[macro_export]
+macro_rules! boxed_in {
+    ($allocator:expr, $value:expr $(,)?) => {
+        $crate::boxed::Box::new_in($value, $allocator)
+    };
+}
@@ -0,0 +1,104 @@
+//! Strategies for choosing a new capacity when a collection runs out of space.
+//!
+//! Geometric growth keeps pushes amortized O(1), at the cost of up to half the buffer
+//! going unused. That trade-off is a poor one for collections backed by a bump or arena
+//! allocator, where abandoned space is never reclaimed, so the policy is a parameter of
+//! [`RawVec`](crate::raw_vec::RawVec).
+
+use core::cmp;
+
+/// Decides how much capacity a buffer should grow to.
+pub trait GrowthPolicy {
+    /// Returns the capacity, in elements, that a buffer currently holding `capacity`
+    /// elements of `elem_size` bytes should grow to so that it can hold at least
+    /// `required` elements.
+    ///
+    /// `required` is always greater than `capacity`. Returning less than `required` is
+    /// not unsafe, but the buffer will grow to exactly `required` instead.
+    fn next_capacity(&self, capacity: usize, required: usize, elem_size: usize) -> usize;
+}
+
+/// Double the capacity on every growth, starting from a small minimum. This is the
+/// default policy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize, elem_size: usize) -> usize {
+        // Avoid a flurry of tiny reallocations when a buffer starts out empty.
+        let min = if elem_size == 1 {
+            8
+        } else if elem_size <= 1024 {
+            4
+        } else {
+            1
+        };
+        cmp::max(min, cmp::max(capacity.saturating_mul(2), required))
+    }
+}
+
+/// Grow the capacity by a factor of roughly 1.6 each time. Memory freed by earlier,
+/// smaller buffers can eventually be reused for a later one, which doubling never allows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GoldenRatio;
+
+impl GrowthPolicy for GoldenRatio {
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize, elem_size: usize) -> usize {
+        let grown = capacity.saturating_add(capacity / 2 + capacity / 8);
+        cmp::max(Doubling.next_capacity(0, required, elem_size), grown)
+    }
+}
+
+/// Grow to exactly the required capacity, never over-allocating. Repeated pushes are
+/// quadratic, so this best suits collections whose final size is known or that grow
+/// rarely.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Exact;
+
+impl GrowthPolicy for Exact {
+    #[inline]
+    fn next_capacity(&self, _capacity: usize, required: usize, _elem_size: usize) -> usize {
+        required
+    }
+}
+
+/// Grow in fixed increments of `chunk` elements, rounding the required capacity up to a
+/// multiple of the chunk size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunked {
+    chunk: usize,
+}
+
+impl Chunked {
+    /// # Panics
+    /// Panics if `chunk` is zero.
+    pub const fn new(chunk: usize) -> Self {
+        assert!(chunk != 0, "chunk size must be non-zero");
+        Self { chunk }
+    }
+
+    pub fn chunk(&self) -> usize {
+        self.chunk
+    }
+}
+
+impl GrowthPolicy for Chunked {
+    #[inline]
+    fn next_capacity(&self, _capacity: usize, required: usize, _elem_size: usize) -> usize {
+        required
+            .checked_next_multiple_of(self.chunk)
+            .unwrap_or(required)
+    }
+}
+
+impl<P> GrowthPolicy for &P
+where
+    P: GrowthPolicy + ?Sized,
+{
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize, elem_size: usize) -> usize {
+        (**self).next_capacity(capacity, required, elem_size)
+    }
+}
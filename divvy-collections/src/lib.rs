@@ -1,5 +1,24 @@
 #![no_std]
+#![cfg_attr(
+    feature = "strict_provenance",
+    feature(strict_provenance_lints),
+    deny(fuzzy_provenance_casts, lossy_provenance_casts)
+)]
+#![cfg_attr(
+    feature = "nightly",
+    feature(coerce_unsized, layout_for_ptr, ptr_metadata, unsize)
+)]
 
-// pub mod arc;
+pub mod arc;
+pub mod borrowed_box;
 pub mod boxed;
+pub mod growth;
+pub mod raw_vec;
+pub mod rc;
+pub mod scoped_box;
+pub mod small_box;
+pub mod string;
+#[cfg(feature = "nightly")]
+pub mod thin_box;
+pub mod typed_arena;
 pub mod vec;
@@ -0,0 +1,325 @@
+use core::{
+    alloc::Layout,
+    cmp,
+    fmt::Debug,
+    mem::{self, ManuallyDrop},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+use crate::growth::{Doubling, GrowthPolicy};
+
+/// A buffer of possibly-uninitialized values of type `T`, managing only its capacity.
+///
+/// `RawVec` handles the layout arithmetic, overflow checks, and allocator calls needed to
+/// build growable collections, while leaving the tracking of which slots are initialized to
+/// the caller. Values are never dropped by the buffer; the memory is simply returned to the
+/// allocator when it is dropped.
+///
+/// Zero-sized types never allocate, and have a capacity of `usize::MAX`.
+///
+/// How far the buffer grows when it runs out of space is decided by its
+/// [growth policy](GrowthPolicy), which defaults to [doubling](Doubling).
+pub struct RawVec<T, A, P = Doubling>
+where
+    A: Deallocator,
+{
+    ptr: NonNull<T>,
+    cap: usize,
+    allocator: A,
+    policy: P,
+}
+
+unsafe impl<T, A, P> Send for RawVec<T, A, P>
+where
+    T: Send,
+    A: Deallocator + Send,
+    P: Send,
+{
+}
+
+unsafe impl<T, A, P> Sync for RawVec<T, A, P>
+where
+    T: Sync,
+    A: Deallocator + Sync,
+    P: Sync,
+{
+}
+
+impl<T, A> RawVec<T, A>
+where
+    A: Deallocator,
+{
+    /// Create an empty buffer without allocating.
+    pub const fn new_in(allocator: A) -> Self {
+        Self::with_policy_in(Doubling, allocator)
+    }
+}
+
+impl<T, A> RawVec<T, A>
+where
+    A: Allocator,
+{
+    pub fn try_with_capacity_in(capacity: usize, allocator: A) -> Result<Self, AllocError> {
+        Self::try_with_capacity_and_policy_in(capacity, Doubling, allocator)
+    }
+
+    pub fn with_capacity_in(capacity: usize, allocator: A) -> Self {
+        Self::try_with_capacity_in(capacity, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, A, P> RawVec<T, A, P>
+where
+    A: Deallocator,
+{
+    const IS_ZST: bool = mem::size_of::<T>() == 0;
+
+    /// Create an empty buffer that grows according to `policy`, without allocating.
+    pub const fn with_policy_in(policy: P, allocator: A) -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            cap: if Self::IS_ZST { usize::MAX } else { 0 },
+            allocator,
+            policy,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by `allocator` with the layout of an array of
+    /// `capacity` values of `T`, or `capacity` must be zero. Zero-sized types ignore
+    /// `capacity` entirely.
+    pub unsafe fn from_raw_parts_in(
+        ptr: NonNull<T>,
+        capacity: usize,
+        policy: P,
+        allocator: A,
+    ) -> Self {
+        Self {
+            ptr,
+            cap: if Self::IS_ZST { usize::MAX } else { capacity },
+            allocator,
+            policy,
+        }
+    }
+
+    /// Decompose the buffer into its pointer, capacity, growth policy, and allocator,
+    /// without deallocating.
+    pub fn into_raw_parts(self) -> (NonNull<T>, usize, P, A) {
+        let this = ManuallyDrop::new(self);
+        let policy = unsafe { ptr::read(&this.policy) };
+        let allocator = unsafe { ptr::read(&this.allocator) };
+        (this.ptr, this.cap, policy, allocator)
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    pub fn as_non_null(&self) -> NonNull<T> {
+        self.ptr
+    }
+
+    /// Returns the number of values the buffer can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Returns the current block and its layout, or `None` if nothing is allocated.
+    fn current_memory(&self) -> Option<(NonNull<u8>, NonZeroLayout)> {
+        if Self::IS_ZST || self.cap == 0 {
+            return None;
+        }
+        // The layout was valid when the block was allocated.
+        let layout = unsafe {
+            Layout::from_size_align_unchecked(self.cap * mem::size_of::<T>(), mem::align_of::<T>())
+        };
+        Some((self.ptr.cast(), NonZeroLayout::new(layout)?))
+    }
+
+    /// Record a block returned by the allocator, making use of any slack it reports.
+    #[inline]
+    fn set_memory(&mut self, ptr: NonNull<u8>, size: usize) {
+        self.ptr = ptr.cast();
+        self.cap = size / mem::size_of::<T>();
+    }
+}
+
+impl<T, A, P> RawVec<T, A, P>
+where
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    pub fn try_with_capacity_and_policy_in(
+        capacity: usize,
+        policy: P,
+        allocator: A,
+    ) -> Result<Self, AllocError> {
+        let mut this = Self::with_policy_in(policy, allocator);
+        if Self::IS_ZST || capacity == 0 {
+            return Ok(this);
+        }
+
+        let layout = NonZeroLayout::array::<T>(capacity).ok_or(AllocError)?;
+        let (ptr, size) = this.allocator.allocate_at_least(layout)?;
+        this.set_memory(ptr, size);
+        Ok(this)
+    }
+
+    pub fn with_capacity_and_policy_in(capacity: usize, policy: P, allocator: A) -> Self {
+        Self::try_with_capacity_and_policy_in(capacity, policy, allocator)
+            .expect("allocation failed")
+    }
+
+    /// Ensure that the buffer can hold at least `len + additional` values, growing
+    /// according to the buffer's policy to amortize the cost of repeated calls.
+    #[inline]
+    pub fn try_reserve(&mut self, len: usize, additional: usize) -> Result<(), AllocError> {
+        if self.needs_to_grow(len, additional) {
+            self.grow_amortized(len, additional)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, len: usize, additional: usize) {
+        self.try_reserve(len, additional)
+            .expect("allocation failed")
+    }
+
+    /// Ensure that the buffer can hold at least `len + additional` values, without
+    /// deliberately over-allocating.
+    pub fn try_reserve_exact(&mut self, len: usize, additional: usize) -> Result<(), AllocError> {
+        if self.needs_to_grow(len, additional) {
+            let cap = len.checked_add(additional).ok_or(AllocError)?;
+            self.grow_to(cap)?;
+        }
+        Ok(())
+    }
+
+    pub fn reserve_exact(&mut self, len: usize, additional: usize) {
+        self.try_reserve_exact(len, additional)
+            .expect("allocation failed")
+    }
+
+    /// Grow the buffer to hold at least `len + additional` values, choosing the new
+    /// capacity with the buffer's growth policy. Unlike [try_reserve](Self::try_reserve),
+    /// this always reallocates, so callers should check the capacity first.
+    #[cold]
+    pub fn grow_amortized(&mut self, len: usize, additional: usize) -> Result<(), AllocError> {
+        if Self::IS_ZST {
+            // The capacity is already `usize::MAX`, so the request must have overflowed.
+            return Err(AllocError);
+        }
+
+        let required = len.checked_add(additional).ok_or(AllocError)?;
+        let cap = self
+            .policy
+            .next_capacity(self.cap, required, mem::size_of::<T>());
+        self.grow_to(cmp::max(cap, required))
+    }
+
+    /// Shrink the buffer to hold `capacity` values, deallocating it entirely if
+    /// `capacity` is zero. Does nothing if the buffer is already smaller.
+    ///
+    /// If the allocator can shrink the block in place but only to a coarser size, the
+    /// buffer keeps the reported slack, so its capacity may remain above `capacity`.
+    pub fn try_shrink_to_fit(&mut self, capacity: usize) -> Result<(), AllocError> {
+        if Self::IS_ZST || capacity >= self.cap {
+            return Ok(());
+        }
+        let Some((ptr, old_layout)) = self.current_memory() else {
+            return Ok(());
+        };
+
+        match NonZeroLayout::array::<T>(capacity) {
+            Some(new_layout) => {
+                // Shrinking in place may leave some slack, which is kept as capacity.
+                match unsafe {
+                    self.allocator
+                        .try_shrink_at_least(ptr, old_layout, new_layout)
+                } {
+                    Ok(size) => self.set_memory(ptr, size),
+                    Err(_) => {
+                        let ptr = unsafe { self.allocator.shrink(ptr, old_layout, new_layout)? };
+                        self.set_memory(ptr, new_layout.size());
+                    }
+                }
+            }
+            None => {
+                unsafe { self.allocator.deallocate(ptr, old_layout) };
+                self.ptr = NonNull::dangling();
+                self.cap = 0;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn shrink_to_fit(&mut self, capacity: usize) {
+        self.try_shrink_to_fit(capacity).expect("allocation failed")
+    }
+
+    #[inline]
+    fn needs_to_grow(&self, len: usize, additional: usize) -> bool {
+        additional > self.cap.wrapping_sub(len)
+    }
+
+    fn grow_to(&mut self, capacity: usize) -> Result<(), AllocError> {
+        let new_layout = NonZeroLayout::array::<T>(capacity).ok_or(AllocError)?;
+        if new_layout.size() > isize::MAX as usize {
+            return Err(AllocError);
+        }
+
+        match self.current_memory() {
+            Some((ptr, old_layout)) => {
+                let (ptr, size) =
+                    unsafe { self.allocator.grow_at_least(ptr, old_layout, new_layout)? };
+                self.set_memory(ptr, size);
+            }
+            None => {
+                let (ptr, size) = self.allocator.allocate_at_least(new_layout)?;
+                self.set_memory(ptr, size);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, A, P> Drop for RawVec<T, A, P>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        if let Some((ptr, layout)) = self.current_memory() {
+            unsafe { self.allocator.deallocate(ptr, layout) };
+        }
+    }
+}
+
+impl<T, A, P> Debug for RawVec<T, A, P>
+where
+    A: Deallocator + Debug,
+    P: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RawVec")
+            .field("ptr", &self.ptr)
+            .field("capacity", &self.cap)
+            .field("allocator", &self.allocator)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
@@ -0,0 +1,481 @@
+use core::{
+    alloc::Layout,
+    any::Any,
+    cell::Cell,
+    fmt::{Debug, Display},
+    hash::Hash,
+    mem::{self, ManuallyDrop},
+    ops::Deref,
+    ptr::{self, NonNull},
+};
+#[cfg(feature = "nightly")]
+use core::{marker::Unsize, ops::CoerceUnsized};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+use crate::{boxed::Box, vec::Vec};
+
+/// Clones past this count panic, as the count would otherwise be able to overflow.
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+#[repr(C)]
+struct RcInner<T>
+where
+    T: ?Sized,
+{
+    strong: Cell<usize>,
+    value: T,
+}
+
+/// A single-threaded reference-counted pointer. See [`Arc`](crate::arc::Arc) for the
+/// thread-safe version.
+///
+/// The count and the value share a single allocation from `A`. Every clone of an `Rc`
+/// holds a clone of the allocator, and the last one to be dropped frees the allocation
+/// with it, so `A` is usually a reference or a shared handle.
+pub struct Rc<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    ptr: NonNull<RcInner<T>>,
+    allocator: A,
+}
+
+impl<T, A> Rc<T, A>
+where
+    A: Allocator,
+{
+    pub fn try_new_in(value: T, allocator: A) -> Result<Self, AllocError> {
+        let layout = NonZeroLayout::of::<RcInner<T>>().unwrap();
+        let ptr = allocator.allocate(layout)?.cast::<RcInner<T>>();
+        unsafe {
+            ptr.as_ptr().write(RcInner {
+                strong: Cell::new(1),
+                value,
+            })
+        };
+        Ok(Self { ptr, allocator })
+    }
+
+    pub fn new_in(value: T, allocator: A) -> Self {
+        Self::try_new_in(value, allocator).expect("allocation failed")
+    }
+
+    /// Move the value out of a box into a new `Rc`, keeping the box's allocator.
+    ///
+    /// The count has to live in front of the value, so the value is moved into a new
+    /// allocation and the box's memory is freed. If that allocation fails, the box is
+    /// returned unchanged.
+    pub fn try_from_box(b: Box<T, A>) -> Result<Self, Box<T, A>> {
+        let layout = NonZeroLayout::of::<RcInner<T>>().unwrap();
+        let Ok(ptr) = Box::allocator(&b).allocate(layout) else {
+            return Err(b);
+        };
+        let ptr = ptr.cast::<RcInner<T>>();
+        let (value, allocator) = Box::into_inner_with_allocator(b);
+        unsafe {
+            ptr.as_ptr().write(RcInner {
+                strong: Cell::new(1),
+                value,
+            })
+        };
+        Ok(Self { ptr, allocator })
+    }
+
+    pub fn from_box(b: Box<T, A>) -> Self {
+        match Self::try_from_box(b) {
+            Ok(this) => this,
+            Err(_) => panic!("allocation failed"),
+        }
+    }
+}
+
+impl<T, A> Rc<T, A>
+where
+    A: Deallocator,
+{
+    /// Free the allocation of an `Rc` whose count has already been released, returning
+    /// its value and allocator.
+    ///
+    /// # Safety
+    ///
+    /// No other `Rc` may point to the allocation.
+    unsafe fn into_unique_parts(this: Rc<T, A>) -> (T, A) {
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            let value = ptr::addr_of!((*this.ptr.as_ptr()).value).read();
+            let allocator = ptr::read(&this.allocator);
+            let layout = NonZeroLayout::of::<RcInner<T>>().unwrap();
+            allocator.deallocate(this.ptr.cast(), layout);
+            (value, allocator)
+        }
+    }
+
+    /// Returns the value if this is the only `Rc` pointing to it, or the `Rc` itself
+    /// otherwise.
+    pub fn try_unwrap(this: Rc<T, A>) -> Result<T, Rc<T, A>> {
+        Rc::try_unwrap_with_allocator(this).map(|(value, _)| value)
+    }
+
+    fn try_unwrap_with_allocator(this: Rc<T, A>) -> Result<(T, A), Rc<T, A>> {
+        let strong = &this.inner().strong;
+        if strong.get() != 1 {
+            return Err(this);
+        }
+        strong.set(0);
+        Ok(unsafe { Rc::into_unique_parts(this) })
+    }
+
+    /// Returns the value if this is the last `Rc` pointing to it, dropping this `Rc`
+    /// either way.
+    pub fn into_inner(this: Rc<T, A>) -> Option<T> {
+        Rc::try_unwrap(this).ok()
+    }
+}
+
+impl<T, A> Rc<[T], A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn slice_layout(len: usize) -> Result<NonZeroLayout, AllocError> {
+        let array = Layout::array::<T>(len).map_err(|_| AllocError)?;
+        let (layout, _) = NonZeroLayout::of::<RcInner<()>>()
+            .unwrap()
+            .extend(array)
+            .ok_or(AllocError)?;
+        Ok(layout.pad_to_align())
+    }
+
+    /// Allocate an `Rc` for `len` values, with its count initialized and its values not.
+    fn try_allocate_slice(len: usize, allocator: &A) -> Result<NonNull<RcInner<[T]>>, AllocError> {
+        let ptr = allocator.allocate(Self::slice_layout(len)?)?;
+        let ptr = ptr::slice_from_raw_parts_mut(ptr.as_ptr().cast::<T>(), len) as *mut RcInner<[T]>;
+        unsafe {
+            ptr::addr_of_mut!((*ptr).strong).write(Cell::new(1));
+            Ok(NonNull::new_unchecked(ptr))
+        }
+    }
+
+    /// Clone the values of a slice into a single new allocation.
+    pub fn try_from_slice_in(slice: &[T], allocator: A) -> Result<Self, AllocError>
+    where
+        T: Clone,
+    {
+        let ptr = Self::try_allocate_slice(slice.len(), &allocator)?;
+
+        // Frees the allocation, and drops the clones made so far, if a clone panics.
+        struct Guard<'a, T, A: Deallocator> {
+            ptr: NonNull<RcInner<[T]>>,
+            len: usize,
+            allocator: &'a A,
+            layout: NonZeroLayout,
+        }
+
+        impl<T, A: Deallocator> Drop for Guard<'_, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    let values = ptr::addr_of_mut!((*self.ptr.as_ptr()).value).cast::<T>();
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(values, self.len));
+                    self.allocator.deallocate(self.ptr.cast(), self.layout);
+                }
+            }
+        }
+
+        let mut guard = Guard {
+            ptr,
+            len: 0,
+            allocator: &allocator,
+            layout: Self::slice_layout(slice.len())?,
+        };
+        let values = unsafe { ptr::addr_of_mut!((*ptr.as_ptr()).value).cast::<T>() };
+        for value in slice {
+            unsafe { values.add(guard.len).write(value.clone()) };
+            guard.len += 1;
+        }
+        mem::forget(guard);
+
+        Ok(Self { ptr, allocator })
+    }
+
+    pub fn from_slice_in(slice: &[T], allocator: A) -> Self
+    where
+        T: Clone,
+    {
+        Self::try_from_slice_in(slice, allocator).expect("allocation failed")
+    }
+
+    /// Collect the items of an iterator into a single new allocation.
+    ///
+    /// The items are first collected into a temporary buffer from the same allocator, as
+    /// the length of an arbitrary iterator is not known up front.
+    pub fn try_from_iter_in<I>(iter: I, allocator: A) -> Result<Self, AllocError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut items = Vec::new_in(&allocator);
+        items.try_extend(iter)?;
+
+        let ptr = Self::try_allocate_slice(items.len(), &allocator)?;
+        unsafe {
+            let values = ptr::addr_of_mut!((*ptr.as_ptr()).value).cast::<T>();
+            ptr::copy_nonoverlapping(items.as_ptr(), values, items.len());
+            items.set_len(0);
+        }
+        drop(items);
+
+        Ok(Self { ptr, allocator })
+    }
+
+    pub fn from_iter_in<I>(iter: I, allocator: A) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::try_from_iter_in(iter, allocator).expect("allocation failed")
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T, A> Rc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    /// Move a value into a new `Rc`, coercing it to the unsized type `T`, such as a
+    /// trait object.
+    pub fn try_new_unsize_in<U>(value: U, allocator: A) -> Result<Self, AllocError>
+    where
+        U: Unsize<T>,
+    {
+        let this = ManuallyDrop::new(Rc::<U, A>::try_new_in(value, allocator)?);
+        let ptr: NonNull<RcInner<T>> = this.ptr;
+        Ok(Self {
+            ptr,
+            allocator: unsafe { ptr::read(&this.allocator) },
+        })
+    }
+
+    pub fn new_unsize_in<U>(value: U, allocator: A) -> Self
+    where
+        U: Unsize<T>,
+    {
+        Self::try_new_unsize_in(value, allocator).expect("allocation failed")
+    }
+}
+
+impl<A> Rc<dyn Any, A>
+where
+    A: Deallocator,
+{
+    /// Attempt to downcast the value to a concrete type, returning the `Rc` unchanged
+    /// if it is of a different type.
+    pub fn downcast<T>(self) -> Result<Rc<T, A>, Self>
+    where
+        T: Any,
+    {
+        if !(*self).is::<T>() {
+            return Err(self);
+        }
+        let this = ManuallyDrop::new(self);
+        Ok(Rc {
+            ptr: this.ptr.cast(),
+            allocator: unsafe { ptr::read(&this.allocator) },
+        })
+    }
+}
+
+impl<A> Rc<str, A>
+where
+    A: Allocator,
+{
+    pub fn try_from_str_in(s: &str, allocator: A) -> Result<Self, AllocError> {
+        let bytes = Rc::<[u8], A>::try_from_slice_in(s.as_bytes(), allocator)?;
+        let bytes = ManuallyDrop::new(bytes);
+        Ok(Self {
+            ptr: unsafe { NonNull::new_unchecked(bytes.ptr.as_ptr() as *mut RcInner<str>) },
+            allocator: unsafe { ptr::read(&bytes.allocator) },
+        })
+    }
+
+    pub fn from_str_in(s: &str, allocator: A) -> Self {
+        Self::try_from_str_in(s, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, A> Rc<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    #[inline]
+    fn inner(&self) -> &RcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn allocator(this: &Rc<T, A>) -> &A {
+        &this.allocator
+    }
+
+    /// The number of `Rc`s pointing to this value.
+    #[inline]
+    pub fn strong_count(this: &Rc<T, A>) -> usize {
+        this.inner().strong.get()
+    }
+
+    /// Returns true if both `Rc`s point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Rc<T, A>, other: &Rc<T, A>) -> bool {
+        ptr::addr_eq(this.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+
+    /// Returns a mutable reference to the value if no other `Rc` points to it.
+    #[inline]
+    pub fn get_mut(this: &mut Rc<T, A>) -> Option<&mut T> {
+        if this.inner().strong.get() == 1 {
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T, U, A> CoerceUnsized<Rc<U, A>> for Rc<T, A>
+where
+    T: ?Sized + Unsize<U>,
+    U: ?Sized,
+    A: Deallocator,
+{
+}
+
+impl<T, A> Clone for Rc<T, A>
+where
+    T: ?Sized,
+    A: Deallocator + Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        let strong = &self.inner().strong;
+        if strong.get() > MAX_REFCOUNT {
+            panic!("reference count overflow");
+        }
+        strong.set(strong.get() + 1);
+        Self {
+            ptr: self.ptr,
+            allocator: self.allocator.clone(),
+        }
+    }
+}
+
+impl<T, A> Deref for Rc<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner().value
+    }
+}
+
+impl<T, A> Drop for Rc<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        let strong = &self.inner().strong;
+        strong.set(strong.get() - 1);
+        if strong.get() != 0 {
+            return;
+        }
+
+        let layout = Layout::for_value(self.inner());
+        unsafe {
+            ptr::drop_in_place(ptr::addr_of_mut!((*self.ptr.as_ptr()).value));
+            self.allocator
+                .deallocate(self.ptr.cast(), NonZeroLayout::new(layout).unwrap());
+        }
+    }
+}
+
+impl<T, A> Debug for Rc<T, A>
+where
+    T: ?Sized + Debug,
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Debug::fmt(v, f)
+    }
+}
+
+impl<T, A> Display for Rc<T, A>
+where
+    T: ?Sized + Display,
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Display::fmt(v, f)
+    }
+}
+
+impl<T, A> Hash for Rc<T, A>
+where
+    T: ?Sized + Hash,
+    A: Deallocator,
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
+
+/// Moves the value into a new allocation, since the count has to live in front of it.
+/// Panics if the allocation fails; see [try_from_box](Rc::try_from_box).
+impl<T, A> From<Box<T, A>> for Rc<T, A>
+where
+    A: Allocator,
+{
+    fn from(b: Box<T, A>) -> Self {
+        Rc::from_box(b)
+    }
+}
+
+/// Moves the value into a box if the `Rc` is uniquely owned. The value is copied
+/// into a new allocation, and the `Rc`'s memory is freed.
+///
+/// Returns the `Rc` unchanged if it is shared, or if allocating the box fails.
+impl<T, A> TryFrom<Rc<T, A>> for Box<T, A>
+where
+    A: Allocator,
+{
+    type Error = Rc<T, A>;
+
+    fn try_from(this: Rc<T, A>) -> Result<Self, Self::Error> {
+        if Rc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+
+        // Allocate before unwrapping, so that the `Rc` can be handed back on failure.
+        let ptr = match NonZeroLayout::new(Layout::new::<T>()) {
+            Some(layout) => match this.allocator.allocate(layout) {
+                Ok(ptr) => ptr.cast::<T>(),
+                Err(AllocError) => return Err(this),
+            },
+            None => NonNull::dangling(),
+        };
+
+        let Ok((value, allocator)) = Rc::try_unwrap_with_allocator(this) else {
+            unreachable!("the `Rc` is uniquely owned");
+        };
+        unsafe {
+            ptr.as_ptr().write(value);
+            Ok(Box::from_raw_in(ptr.as_ptr(), allocator))
+        }
+    }
+}
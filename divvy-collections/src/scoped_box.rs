@@ -0,0 +1,176 @@
+use core::{
+    alloc::Layout,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+/// An owned value whose memory is managed by someone else.
+///
+/// Dropping a `ScopedBox` runs the value's destructor but never deallocates, making it
+/// the building block for values in arenas that free their memory all at once. Unlike
+/// [`BorrowedBox`](crate::borrowed_box::BorrowedBox), the box does not borrow its
+/// allocator, so it can be stored in structures that cannot carry a lifetime. In
+/// exchange, keeping the memory alive is the caller's responsibility, and creating a box
+/// is unsafe.
+///
+/// Memory that should be returned individually can be with
+/// [dealloc_in](ScopedBox::dealloc_in).
+pub struct ScopedBox<T>
+where
+    T: ?Sized,
+{
+    ptr: NonNull<T>,
+    _p: PhantomData<T>,
+}
+
+unsafe impl<T> Send for ScopedBox<T> where T: ?Sized + Send {}
+unsafe impl<T> Sync for ScopedBox<T> where T: ?Sized + Sync {}
+
+impl<T> ScopedBox<T> {
+    /// Move a value into memory from `allocator`.
+    ///
+    /// # Safety
+    ///
+    /// The memory must remain valid for as long as the box is used, which is to say that
+    /// the allocator must not free or reuse it until the box has been dropped.
+    #[inline]
+    pub unsafe fn try_new_in<A>(value: T, allocator: &A) -> Result<Self, AllocError>
+    where
+        A: Allocator + ?Sized,
+    {
+        let ptr = match NonZeroLayout::new(Layout::new::<T>()) {
+            Some(layout) => allocator.allocate(layout)?.cast::<T>(),
+            None => NonNull::dangling(),
+        };
+        unsafe {
+            ptr.as_ptr().write(value);
+            Ok(Self::from_raw(ptr.as_ptr()))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// See [try_new_in](ScopedBox::try_new_in).
+    #[inline]
+    pub unsafe fn new_in<A>(value: T, allocator: &A) -> Self
+    where
+        A: Allocator + ?Sized,
+    {
+        unsafe { Self::try_new_in(value, allocator) }.expect("allocation failed")
+    }
+
+    /// Move the value out of the box. Its memory is left to the allocator.
+    pub fn into_inner(b: ScopedBox<T>) -> T {
+        let b = ManuallyDrop::new(b);
+        unsafe { b.ptr.as_ptr().read() }
+    }
+}
+
+impl<T> ScopedBox<T>
+where
+    T: ?Sized,
+{
+    /// # Safety
+    ///
+    /// The pointer must refer to a valid, initialized value that is owned by the caller,
+    /// and must remain valid for as long as the box is used.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            _p: PhantomData,
+        }
+    }
+
+    /// Release ownership of the value without dropping it.
+    #[inline]
+    pub fn into_raw(b: ScopedBox<T>) -> *mut T {
+        ManuallyDrop::new(b).ptr.as_ptr()
+    }
+
+    /// Drop the value, then return its memory to `allocator`.
+    ///
+    /// # Safety
+    ///
+    /// The memory must have been allocated by `allocator`, with the layout of the value.
+    pub unsafe fn dealloc_in<A>(b: ScopedBox<T>, allocator: &A)
+    where
+        A: Deallocator + ?Sized,
+    {
+        let layout = Layout::for_value::<T>(&b);
+        let ptr = Self::into_raw(b);
+        unsafe {
+            ptr::drop_in_place(ptr);
+            if let Some(layout) = NonZeroLayout::new(layout) {
+                allocator.deallocate(NonNull::new_unchecked(ptr).cast(), layout);
+            }
+        }
+    }
+}
+
+impl<T> Deref for ScopedBox<T>
+where
+    T: ?Sized,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for ScopedBox<T>
+where
+    T: ?Sized,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> Drop for ScopedBox<T>
+where
+    T: ?Sized,
+{
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.ptr.as_ptr()) };
+    }
+}
+
+impl<T> Debug for ScopedBox<T>
+where
+    T: ?Sized + Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Debug::fmt(v, f)
+    }
+}
+
+impl<T> Display for ScopedBox<T>
+where
+    T: ?Sized + Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Display::fmt(v, f)
+    }
+}
+
+impl<T> Hash for ScopedBox<T>
+where
+    T: ?Sized + Hash,
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
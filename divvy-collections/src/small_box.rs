@@ -0,0 +1,384 @@
+use core::{
+    alloc::Layout,
+    fmt::Debug,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+#[cfg(feature = "nightly")]
+use core::{marker::Unsize, ptr::Pointee};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+use crate::boxed::Box;
+
+mod sealed {
+    use core::alloc::Layout;
+
+    /// A type whose pointer metadata can be stored apart from its address.
+    ///
+    /// Every sized type implements this. With the `nightly` feature, so does every
+    /// unsized type, such as slices and trait objects.
+    pub trait Value {
+        type Meta: Copy;
+
+        fn metadata(ptr: *const Self) -> Self::Meta;
+
+        fn from_raw_parts(addr: *mut u8, meta: Self::Meta) -> *mut Self;
+
+        fn layout(meta: Self::Meta) -> Layout;
+    }
+}
+
+use sealed::Value;
+
+#[cfg(not(feature = "nightly"))]
+impl<T> Value for T {
+    type Meta = ();
+
+    #[inline]
+    fn metadata(_: *const Self) -> Self::Meta {}
+
+    #[inline]
+    fn from_raw_parts(addr: *mut u8, _: Self::Meta) -> *mut Self {
+        addr.cast()
+    }
+
+    #[inline]
+    fn layout(_: Self::Meta) -> Layout {
+        Layout::new::<T>()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T> Value for T
+where
+    T: ?Sized,
+{
+    type Meta = <T as Pointee>::Metadata;
+
+    #[inline]
+    fn metadata(ptr: *const Self) -> Self::Meta {
+        ptr::metadata(ptr)
+    }
+
+    #[inline]
+    fn from_raw_parts(addr: *mut u8, meta: Self::Meta) -> *mut Self {
+        ptr::from_raw_parts_mut(addr, meta)
+    }
+
+    #[inline]
+    fn layout(meta: Self::Meta) -> Layout {
+        // The metadata always comes from a valid value, so its layout is valid as well.
+        unsafe { Layout::for_value_raw(ptr::from_raw_parts::<T>(ptr::null::<u8>(), meta)) }
+    }
+}
+
+/// A box that stores small values inline, and only allocates for values that do not
+/// fit.
+///
+/// Values of up to `N` bytes, with an alignment no greater than that of `usize`, are
+/// stored within the `SmallBox` itself. Larger or more strictly aligned values are
+/// stored in memory from the allocator, exactly as with [`Box`].
+///
+/// With the `nightly` feature, `T` may also be unsized, such as `SmallBox<dyn Trait, N,
+/// A>` or `SmallBox<[T], N, A>`. The pointer metadata (the vtable or length) is kept
+/// next to the storage, and whether a value is stored inline is decided per value from
+/// its own size and alignment. For sized types the metadata takes up no space.
+pub struct SmallBox<T, const N: usize, A>
+where
+    T: ?Sized + Value,
+    A: Deallocator,
+{
+    storage: Storage<N>,
+    meta: T::Meta,
+    allocator: A,
+    _p: PhantomData<T>,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+union Storage<const N: usize> {
+    inline: Inline<N>,
+    heap: NonNull<u8>,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Inline<const N: usize> {
+    _align: [usize; 0],
+    bytes: [MaybeUninit<u8>; N],
+}
+
+impl<T, const N: usize, A> SmallBox<T, N, A>
+where
+    T: ?Sized + Value,
+    A: Deallocator,
+{
+    /// Returns `true` if a value with this layout is stored inline.
+    #[inline]
+    const fn fits(layout: Layout) -> bool {
+        layout.size() <= N && layout.align() <= mem::align_of::<Inline<N>>()
+    }
+
+    /// Returns `true` if the value is stored within the `SmallBox` itself.
+    #[inline]
+    pub fn is_inline(b: &SmallBox<T, N, A>) -> bool {
+        Self::fits(T::layout(b.meta))
+    }
+
+    pub fn allocator(b: &SmallBox<T, N, A>) -> &A {
+        &b.allocator
+    }
+
+    /// Convert a regular `Box` into a `SmallBox`. If the value fits inline, it is moved
+    /// out of the box and the box's memory is deallocated.
+    pub fn from_box(b: Box<T, A>) -> Self {
+        let (ptr, allocator) = Box::into_raw_with_allocator(b);
+        let meta = T::metadata(ptr);
+        let layout = T::layout(meta);
+
+        let storage = if Self::fits(layout) {
+            let mut storage = Storage {
+                inline: Inline {
+                    _align: [],
+                    bytes: [MaybeUninit::uninit(); N],
+                },
+            };
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    ptr.cast::<u8>(),
+                    ptr::addr_of_mut!(storage.inline.bytes).cast::<u8>(),
+                    layout.size(),
+                );
+                if let Some(layout) = NonZeroLayout::new(layout) {
+                    allocator.deallocate(NonNull::new_unchecked(ptr.cast()), layout);
+                }
+            }
+            storage
+        } else {
+            Storage {
+                heap: unsafe { NonNull::new_unchecked(ptr.cast()) },
+            }
+        };
+
+        Self {
+            storage,
+            meta,
+            allocator,
+            _p: PhantomData,
+        }
+    }
+
+    fn into_heap_box(b: SmallBox<T, N, A>) -> Box<T, A> {
+        debug_assert!(!Self::is_inline(&b));
+
+        let b = ManuallyDrop::new(b);
+        let allocator = unsafe { ptr::read(&b.allocator) };
+        let ptr = T::from_raw_parts(unsafe { b.storage.heap.as_ptr() }, b.meta);
+        unsafe { Box::from_raw_in(ptr, allocator) }
+    }
+
+    #[inline]
+    fn as_ptr(&self) -> *const T {
+        let addr = if Self::is_inline(self) {
+            unsafe { ptr::addr_of!(self.storage.inline.bytes) }
+                .cast::<u8>()
+                .cast_mut()
+        } else {
+            unsafe { self.storage.heap.as_ptr() }
+        };
+        T::from_raw_parts(addr, self.meta)
+    }
+
+    #[inline]
+    fn as_mut_ptr(&mut self) -> *mut T {
+        let addr = if Self::is_inline(self) {
+            unsafe { ptr::addr_of_mut!(self.storage.inline.bytes) }.cast::<u8>()
+        } else {
+            unsafe { self.storage.heap.as_ptr() }
+        };
+        T::from_raw_parts(addr, self.meta)
+    }
+}
+
+impl<T, const N: usize, A> SmallBox<T, N, A>
+where
+    A: Deallocator,
+{
+    const INLINE: bool = Self::fits(Layout::new::<T>());
+
+    pub fn into_inner(b: SmallBox<T, N, A>) -> T {
+        if Self::INLINE {
+            let b = ManuallyDrop::new(b);
+            let value = unsafe { b.as_ptr().read() };
+            drop(unsafe { ptr::read(&b.allocator) });
+            value
+        } else {
+            Box::into_inner(Self::into_heap_box(b))
+        }
+    }
+
+    fn inline(value: T, allocator: A) -> Self {
+        let mut storage = Storage {
+            inline: Inline {
+                _align: [],
+                bytes: [MaybeUninit::uninit(); N],
+            },
+        };
+        unsafe {
+            ptr::addr_of_mut!(storage.inline.bytes)
+                .cast::<T>()
+                .write(value)
+        };
+
+        Self {
+            storage,
+            meta: T::metadata(ptr::null()),
+            allocator,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize, A> SmallBox<T, N, A>
+where
+    A: Allocator,
+{
+    #[inline]
+    pub fn try_new_in(value: T, allocator: A) -> Result<Self, AllocError> {
+        if Self::INLINE {
+            Ok(Self::inline(value, allocator))
+        } else {
+            Ok(Self::from_box(Box::try_new_in(value, allocator)?))
+        }
+    }
+
+    #[inline]
+    pub fn new_in(value: T, allocator: A) -> Self {
+        Self::try_new_in(value, allocator).expect("allocation failed")
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T, const N: usize, A> SmallBox<T, N, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    /// Move a value into a `SmallBox`, coercing it to the unsized type `T`, such as a
+    /// trait object. The value is stored inline if `U` fits.
+    pub fn try_new_unsize_in<U>(value: U, allocator: A) -> Result<Self, AllocError>
+    where
+        U: Unsize<T>,
+    {
+        let meta = ptr::metadata(ptr::null::<U>() as *const T);
+        let b = ManuallyDrop::new(SmallBox::<U, N, A>::try_new_in(value, allocator)?);
+        Ok(Self {
+            storage: b.storage,
+            meta,
+            allocator: unsafe { ptr::read(&b.allocator) },
+            _p: PhantomData,
+        })
+    }
+
+    pub fn new_unsize_in<U>(value: U, allocator: A) -> Self
+    where
+        U: Unsize<T>,
+    {
+        Self::try_new_unsize_in(value, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, const N: usize, A> SmallBox<T, N, A>
+where
+    T: ?Sized + Value,
+    A: Allocator,
+{
+    /// Convert this into a regular `Box`, allocating if the value is stored inline. If
+    /// the allocation fails, the `SmallBox` is returned unchanged.
+    pub fn try_into_box(b: SmallBox<T, N, A>) -> Result<Box<T, A>, SmallBox<T, N, A>> {
+        if !Self::is_inline(&b) {
+            return Ok(Self::into_heap_box(b));
+        }
+
+        let layout = T::layout(b.meta);
+        let ptr = match NonZeroLayout::new(layout) {
+            Some(layout) => match b.allocator.allocate(layout) {
+                Ok(ptr) => ptr.as_ptr(),
+                Err(AllocError) => return Err(b),
+            },
+            None => ptr::without_provenance_mut(layout.align()),
+        };
+
+        let b = ManuallyDrop::new(b);
+        unsafe {
+            ptr::copy_nonoverlapping(b.as_ptr().cast::<u8>(), ptr, layout.size());
+            let allocator = ptr::read(&b.allocator);
+            Ok(Box::from_raw_in(T::from_raw_parts(ptr, b.meta), allocator))
+        }
+    }
+
+    pub fn into_box(b: SmallBox<T, N, A>) -> Box<T, A> {
+        match Self::try_into_box(b) {
+            Ok(b) => b,
+            Err(_) => panic!("allocation failed"),
+        }
+    }
+}
+
+impl<T, const N: usize, A> Deref for SmallBox<T, N, A>
+where
+    T: ?Sized + Value,
+    A: Deallocator,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.as_ptr() }
+    }
+}
+
+impl<T, const N: usize, A> DerefMut for SmallBox<T, N, A>
+where
+    T: ?Sized + Value,
+    A: Deallocator,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.as_mut_ptr() }
+    }
+}
+
+impl<T, const N: usize, A> Drop for SmallBox<T, N, A>
+where
+    T: ?Sized + Value,
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        let inline = Self::is_inline(self);
+        unsafe { ptr::drop_in_place(self.as_mut_ptr()) };
+        if !inline {
+            let ptr = unsafe { self.storage.heap };
+            if let Some(layout) = NonZeroLayout::new(T::layout(self.meta)) {
+                unsafe { self.allocator.deallocate(ptr, layout) };
+            }
+        }
+    }
+}
+
+impl<T, const N: usize, A> Debug for SmallBox<T, N, A>
+where
+    T: ?Sized + Value + Debug,
+    A: Deallocator + Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        f.debug_struct("SmallBox")
+            .field("value", &v)
+            .field("allocator", &self.allocator)
+            .finish()
+    }
+}
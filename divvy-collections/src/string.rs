@@ -0,0 +1,447 @@
+use core::{
+    fmt::{self, Debug, Display},
+    hash::Hash,
+    ops::{Deref, DerefMut},
+    str,
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator};
+
+use crate::{
+    boxed::Box,
+    growth::{Doubling, GrowthPolicy},
+    vec::Vec,
+};
+
+/// A growable UTF-8 string, with its memory obtained from an allocator.
+///
+/// Strings are most easily built with [`format_in!`](crate::format_in).
+pub struct String<A, P = Doubling>
+where
+    A: Deallocator,
+{
+    vec: Vec<u8, A, P>,
+}
+
+impl<A> String<A>
+where
+    A: Deallocator,
+{
+    pub const fn new_in(allocator: A) -> Self {
+        Self {
+            vec: Vec::new_in(allocator),
+        }
+    }
+}
+
+impl<A> String<A>
+where
+    A: Allocator,
+{
+    pub fn try_with_capacity_in(capacity: usize, allocator: A) -> Result<Self, AllocError> {
+        Ok(Self {
+            vec: Vec::try_with_capacity_in(capacity, allocator)?,
+        })
+    }
+
+    pub fn with_capacity_in(capacity: usize, allocator: A) -> Self {
+        Self::try_with_capacity_in(capacity, allocator).expect("allocation failed")
+    }
+
+    pub fn try_from_str_in(s: &str, allocator: A) -> Result<Self, AllocError> {
+        let mut string = Self::try_with_capacity_in(s.len(), allocator)?;
+        string.try_push_str(s)?;
+        Ok(string)
+    }
+
+    pub fn from_str_in(s: &str, allocator: A) -> Self {
+        Self::try_from_str_in(s, allocator).expect("allocation failed")
+    }
+
+    /// Format `args` into a new string. Prefer the [`try_format_in!`](crate::try_format_in)
+    /// macro.
+    pub fn try_format_in(args: fmt::Arguments<'_>, allocator: A) -> Result<Self, AllocError> {
+        let mut string = Self::new_in(allocator);
+        if let Some(s) = args.as_str() {
+            string.try_push_str(s)?;
+        } else {
+            let mut writer = Writer::new(&mut string.vec);
+            if fmt::Write::write_fmt(&mut writer, args).is_err() {
+                assert!(
+                    writer.alloc_failed,
+                    "a formatting trait implementation returned an error"
+                );
+                return Err(AllocError);
+            }
+        }
+        Ok(string)
+    }
+
+    /// Format `args` into a new string. Prefer the [`format_in!`](crate::format_in)
+    /// macro.
+    pub fn format_in(args: fmt::Arguments<'_>, allocator: A) -> Self {
+        Self::try_format_in(args, allocator).expect("allocation failed")
+    }
+}
+
+impl<A, P> String<A, P>
+where
+    A: Deallocator,
+{
+    /// Create an empty string that grows according to `policy`, without allocating.
+    pub const fn with_policy_in(policy: P, allocator: A) -> Self {
+        Self {
+            vec: Vec::with_policy_in(policy, allocator),
+        }
+    }
+
+    /// Convert a vector of bytes into a string, returning the vector back if it is not
+    /// valid UTF-8.
+    pub fn from_utf8(vec: Vec<u8, A, P>) -> Result<Self, Vec<u8, A, P>> {
+        match str::from_utf8(&vec) {
+            Ok(_) => Ok(Self { vec }),
+            Err(_) => Err(vec),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The bytes must be valid UTF-8.
+    pub unsafe fn from_utf8_unchecked(vec: Vec<u8, A, P>) -> Self {
+        Self { vec }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8, A, P> {
+        self.vec
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.vec) }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        unsafe { str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.vec.truncate(self.len() - c.len_utf8());
+        Some(c)
+    }
+
+    /// Shorten the string to `len` bytes. Does nothing if the string is already shorter.
+    ///
+    /// # Panics
+    /// Panics if `len` does not lie on a character boundary.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            assert!(
+                self.as_str().is_char_boundary(len),
+                "truncation point is not a character boundary"
+            );
+            self.vec.truncate(len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Consume the string without freeing its memory, returning a reference to its
+    /// contents. This is most useful with arena allocators, whose memory is reclaimed all
+    /// at once.
+    pub fn leak<'a>(self) -> &'a mut str
+    where
+        A: 'a,
+    {
+        unsafe { str::from_utf8_unchecked_mut(self.vec.leak()) }
+    }
+}
+
+impl<A, P> String<A, P>
+where
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.vec.try_reserve(additional)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional)
+    }
+
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), AllocError> {
+        self.vec.try_shrink_to_fit()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit()
+    }
+
+    pub fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), AllocError> {
+        self.vec.try_shrink_to(min_capacity)
+    }
+
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.vec.shrink_to(min_capacity)
+    }
+
+    /// Release any excess capacity and convert the string into a boxed `str`. On
+    /// failure, the string is returned unchanged.
+    pub fn try_into_boxed_str(self) -> Result<Box<str, A>, Self> {
+        match self.vec.try_into_boxed_slice() {
+            Ok(b) => {
+                let (ptr, allocator) = Box::into_raw_with_allocator(b);
+                Ok(unsafe { Box::from_raw_in(ptr as *mut str, allocator) })
+            }
+            Err(vec) => Err(Self { vec }),
+        }
+    }
+
+    pub fn into_boxed_str(self) -> Box<str, A> {
+        match self.try_into_boxed_str() {
+            Ok(b) => b,
+            Err(_) => panic!("allocation failed"),
+        }
+    }
+
+    /// Append a string slice. On failure, the string is left unchanged.
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), AllocError> {
+        self.vec.try_extend_from_slice(s.as_bytes())
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.try_push_str(s).expect("allocation failed")
+    }
+
+    /// Append a character. On failure, the string is left unchanged.
+    pub fn try_push(&mut self, c: char) -> Result<(), AllocError> {
+        self.try_push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.try_push(c).expect("allocation failed")
+    }
+
+    /// Clone the string into a new allocation from a clone of its allocator.
+    pub fn try_clone(&self) -> Result<Self, AllocError>
+    where
+        A: Clone,
+        P: Clone,
+    {
+        Ok(Self {
+            vec: self.vec.try_clone()?,
+        })
+    }
+}
+
+impl<A, P> Deref for String<A, P>
+where
+    A: Deallocator,
+{
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<A, P> DerefMut for String<A, P>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<A, P> fmt::Write for String<A, P>
+where
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<A, P> Clone for String<A, P>
+where
+    A: Allocator + Clone,
+    P: GrowthPolicy + Clone,
+{
+    fn clone(&self) -> Self {
+        self.try_clone().expect("allocation failed")
+    }
+}
+
+impl<A, P> Debug for String<A, P>
+where
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<A, P> Display for String<A, P>
+where
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<A, P> Hash for String<A, P>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<A1, A2, P1, P2> PartialEq<String<A2, P2>> for String<A1, P1>
+where
+    A1: Deallocator,
+    A2: Deallocator,
+{
+    #[inline]
+    fn eq(&self, other: &String<A2, P2>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<A, P> Eq for String<A, P> where A: Deallocator {}
+
+impl<A, P> PartialEq<str> for String<A, P>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<A, P> PartialEq<&str> for String<A, P>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// An adapter implementing [`fmt::Write`] over a byte vector, so that formatted text can
+/// be written straight into memory from any allocator.
+///
+/// Unlike writing to a [`String`], which can only report a bare [`fmt::Error`], the
+/// writer remembers whether the failure was caused by the allocator.
+pub struct Writer<'a, A, P = Doubling>
+where
+    A: Deallocator,
+{
+    vec: &'a mut Vec<u8, A, P>,
+    alloc_failed: bool,
+}
+
+impl<'a, A, P> Writer<'a, A, P>
+where
+    A: Deallocator,
+{
+    pub fn new(vec: &'a mut Vec<u8, A, P>) -> Self {
+        Self {
+            vec,
+            alloc_failed: false,
+        }
+    }
+
+    /// Returns `true` if a write failed because the allocator could not provide memory.
+    pub fn alloc_failed(&self) -> bool {
+        self.alloc_failed
+    }
+
+    pub fn into_inner(self) -> &'a mut Vec<u8, A, P> {
+        self.vec
+    }
+}
+
+impl<A, P> fmt::Write for Writer<'_, A, P>
+where
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.vec.try_extend_from_slice(s.as_bytes()).map_err(|_| {
+            self.alloc_failed = true;
+            fmt::Error
+        })
+    }
+}
+
+impl<A, P> Debug for Writer<'_, A, P>
+where
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Writer")
+            .field("len", &self.vec.len())
+            .field("alloc_failed", &self.alloc_failed)
+            .finish()
+    }
+}
+
+/// Format a string into memory from an allocator, like `format!`.
+///
+/// ```ignore
+/// let message = format_in!(&arena, "{} of {} done", done, total);
+/// ```
+///
+/// # Panics
+/// Panics if allocation fails. See [`try_format_in!`](crate::try_format_in) for a fallible
+/// alternative.
+#[macro_export]
+macro_rules! format_in {
+    ($allocator:expr, $($arg:tt)*) => {
+        $crate::string::String::format_in(::core::format_args!($($arg)*), $allocator)
+    };
+}
+
+/// Format a string into memory from an allocator, returning
+/// `Result<String<A>, AllocError>`.
+#[macro_export]
+macro_rules! try_format_in {
+    ($allocator:expr, $($arg:tt)*) => {
+        $crate::string::String::try_format_in(::core::format_args!($($arg)*), $allocator)
+    };
+}
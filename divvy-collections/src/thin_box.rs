@@ -0,0 +1,275 @@
+use core::{
+    alloc::Layout,
+    fmt::{Debug, Display},
+    marker::{PhantomData, Unsize},
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull, Pointee},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+/// A box that is a single pointer wide, even for dynamically sized values.
+///
+/// The pointer metadata of the value, such as the length of a slice or the vtable of a
+/// trait object, is stored in the allocation right before the value rather than in the
+/// handle. This halves the size of each handle at the cost of an extra header in every
+/// allocation, which pays off in dense structures such as interned strings or arrays of
+/// trait objects. Note that the allocator itself is still stored alongside the pointer,
+/// so the handle is only a single pointer when the allocator is zero-sized.
+pub struct ThinBox<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    /// Points to the metadata header, followed by the value.
+    ptr: NonNull<u8>,
+    allocator: A,
+    _p: PhantomData<T>,
+}
+
+unsafe impl<T, A> Send for ThinBox<T, A>
+where
+    T: ?Sized + Send,
+    A: Deallocator + Send,
+{
+}
+
+unsafe impl<T, A> Sync for ThinBox<T, A>
+where
+    T: ?Sized + Sync,
+    A: Deallocator + Sync,
+{
+}
+
+impl<T, A> ThinBox<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    /// Returns the layout of the whole allocation and the offset of the value within it.
+    #[inline]
+    fn layout(meta: <T as Pointee>::Metadata) -> (Layout, usize) {
+        let value = ptr::from_raw_parts::<T>(ptr::null::<u8>(), meta);
+        // The metadata came from a valid value, so its layout is valid as well.
+        let value = unsafe { Layout::for_value_raw(value) };
+        let (layout, offset) = Layout::new::<<T as Pointee>::Metadata>()
+            .extend(value)
+            .unwrap();
+        (layout.pad_to_align(), offset)
+    }
+
+    #[inline]
+    fn meta(&self) -> <T as Pointee>::Metadata {
+        unsafe { self.ptr.cast::<<T as Pointee>::Metadata>().read() }
+    }
+
+    #[inline]
+    fn value_ptr(&self) -> *mut T {
+        let meta = self.meta();
+        let (_, offset) = Self::layout(meta);
+        ptr::from_raw_parts_mut(unsafe { self.ptr.as_ptr().add(offset) }, meta)
+    }
+
+    pub fn allocator(b: &ThinBox<T, A>) -> &A {
+        &b.allocator
+    }
+}
+
+impl<T, A> ThinBox<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    /// Allocate room for a header and a value with the given metadata, writing the
+    /// header. Returns the allocation and the address of the value.
+    fn allocate(
+        meta: <T as Pointee>::Metadata,
+        allocator: &A,
+    ) -> Result<(NonNull<u8>, *mut u8), AllocError> {
+        let (layout, offset) = Self::layout(meta);
+        let ptr = match NonZeroLayout::new(layout) {
+            Some(layout) => allocator.allocate(layout)?,
+            None => unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) },
+        };
+        unsafe {
+            ptr.cast::<<T as Pointee>::Metadata>().write(meta);
+            Ok((ptr, ptr.as_ptr().add(offset)))
+        }
+    }
+
+    /// Move a value into a thin box, coercing it to the unsized type `T`.
+    pub fn try_new_unsize_in<U>(value: U, allocator: A) -> Result<Self, AllocError>
+    where
+        U: Unsize<T>,
+    {
+        let meta = ptr::metadata(&value as &T);
+        let (ptr, value_ptr) = Self::allocate(meta, &allocator)?;
+        unsafe { value_ptr.cast::<U>().write(value) };
+        Ok(Self {
+            ptr,
+            allocator,
+            _p: PhantomData,
+        })
+    }
+
+    pub fn new_unsize_in<U>(value: U, allocator: A) -> Self
+    where
+        U: Unsize<T>,
+    {
+        Self::try_new_unsize_in(value, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, A> ThinBox<T, A>
+where
+    A: Allocator,
+{
+    pub fn try_new_in(value: T, allocator: A) -> Result<Self, AllocError> {
+        let (ptr, value_ptr) = Self::allocate((), &allocator)?;
+        unsafe { value_ptr.cast::<T>().write(value) };
+        Ok(Self {
+            ptr,
+            allocator,
+            _p: PhantomData,
+        })
+    }
+
+    pub fn new_in(value: T, allocator: A) -> Self {
+        Self::try_new_in(value, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, A> ThinBox<[T], A>
+where
+    T: Clone,
+    A: Allocator,
+{
+    pub fn try_from_slice_in(slice: &[T], allocator: A) -> Result<Self, AllocError> {
+        let (ptr, values) = Self::allocate(slice.len(), &allocator)?;
+
+        // Frees the allocation, and drops the clones made so far, if a clone panics.
+        struct Guard<'a, T, A: Deallocator> {
+            ptr: NonNull<u8>,
+            values: *mut T,
+            len: usize,
+            allocator: &'a A,
+            layout: Layout,
+        }
+
+        impl<T, A: Deallocator> Drop for Guard<'_, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.values, self.len));
+                    if let Some(layout) = NonZeroLayout::new(self.layout) {
+                        self.allocator.deallocate(self.ptr, layout);
+                    }
+                }
+            }
+        }
+
+        let mut guard = Guard {
+            ptr,
+            values: values.cast::<T>(),
+            len: 0,
+            allocator: &allocator,
+            layout: Self::layout(slice.len()).0,
+        };
+        for value in slice {
+            unsafe { guard.values.add(guard.len).write(value.clone()) };
+            guard.len += 1;
+        }
+        mem::forget(guard);
+
+        Ok(Self {
+            ptr,
+            allocator,
+            _p: PhantomData,
+        })
+    }
+
+    pub fn from_slice_in(slice: &[T], allocator: A) -> Self {
+        Self::try_from_slice_in(slice, allocator).expect("allocation failed")
+    }
+}
+
+impl<A> ThinBox<str, A>
+where
+    A: Allocator,
+{
+    pub fn try_from_str_in(s: &str, allocator: A) -> Result<Self, AllocError> {
+        let (ptr, bytes) = Self::allocate(s.len(), &allocator)?;
+        unsafe { ptr::copy_nonoverlapping(s.as_ptr(), bytes, s.len()) };
+        Ok(Self {
+            ptr,
+            allocator,
+            _p: PhantomData,
+        })
+    }
+
+    pub fn from_str_in(s: &str, allocator: A) -> Self {
+        Self::try_from_str_in(s, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, A> Deref for ThinBox<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.value_ptr() }
+    }
+}
+
+impl<T, A> DerefMut for ThinBox<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.value_ptr() }
+    }
+}
+
+impl<T, A> Drop for ThinBox<T, A>
+where
+    T: ?Sized,
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        let (layout, _) = Self::layout(self.meta());
+        unsafe {
+            ptr::drop_in_place(self.value_ptr());
+            if let Some(layout) = NonZeroLayout::new(layout) {
+                self.allocator.deallocate(self.ptr, layout);
+            }
+        }
+    }
+}
+
+impl<T, A> Debug for ThinBox<T, A>
+where
+    T: ?Sized + Debug,
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Debug::fmt(v, f)
+    }
+}
+
+impl<T, A> Display for ThinBox<T, A>
+where
+    T: ?Sized + Display,
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v: &T = self;
+        Display::fmt(v, f)
+    }
+}
@@ -0,0 +1,193 @@
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    cmp,
+    fmt::Debug,
+    marker::PhantomData,
+    mem,
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+/// An arena that allocates values of a single type, and runs their destructors when
+/// the arena is dropped or reset.
+///
+/// Values are stored in chunks obtained from the allocator, each twice the capacity of
+/// the last. Values never move once allocated, so references to them remain valid for
+/// as long as the arena is borrowed.
+pub struct TypedArena<T, A>
+where
+    A: Deallocator,
+{
+    chunk: Cell<Option<NonNull<ChunkHeader>>>,
+    allocator: A,
+    _p: PhantomData<T>,
+}
+
+#[repr(C)]
+struct ChunkHeader {
+    prev: Option<NonNull<ChunkHeader>>,
+    capacity: usize,
+    len: usize,
+}
+
+const INITIAL_CHUNK_BYTES: usize = 4096;
+
+impl<T, A> TypedArena<T, A>
+where
+    A: Deallocator,
+{
+    pub const fn new_in(allocator: A) -> Self {
+        Self {
+            chunk: Cell::new(None),
+            allocator,
+            _p: PhantomData,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.allocator
+    }
+
+    /// Returns the number of values in the arena.
+    pub fn len(&self) -> usize {
+        let mut len = 0;
+        let mut chunk = self.chunk.get();
+        while let Some(c) = chunk {
+            unsafe {
+                len += (*c.as_ptr()).len;
+                chunk = (*c.as_ptr()).prev;
+            }
+        }
+        len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every value in the arena. The most recently allocated chunk is kept for
+    /// reuse, and all others are returned to the allocator.
+    pub fn reset(&mut self) {
+        let Some(current) = self.chunk.get() else {
+            return;
+        };
+
+        unsafe {
+            Self::drop_values(current);
+            let prev = (*current.as_ptr()).prev.take();
+            self.free_chunks(prev);
+        }
+    }
+
+    fn chunk_layout(capacity: usize) -> Option<(Layout, usize)> {
+        let array = Layout::array::<T>(capacity).ok()?;
+        let (layout, offset) = Layout::new::<ChunkHeader>().extend(array).ok()?;
+        Some((layout.pad_to_align(), offset))
+    }
+
+    #[inline]
+    unsafe fn values(chunk: NonNull<ChunkHeader>) -> *mut T {
+        let offset = Layout::new::<ChunkHeader>()
+            .extend(Layout::new::<T>())
+            .unwrap()
+            .1;
+        unsafe { chunk.as_ptr().cast::<u8>().add(offset).cast() }
+    }
+
+    unsafe fn drop_values(chunk: NonNull<ChunkHeader>) {
+        unsafe {
+            let len = mem::replace(&mut (*chunk.as_ptr()).len, 0);
+            let values = ptr::slice_from_raw_parts_mut(Self::values(chunk), len);
+            ptr::drop_in_place(values);
+        }
+    }
+
+    unsafe fn free_chunks(&self, mut chunk: Option<NonNull<ChunkHeader>>) {
+        while let Some(c) = chunk {
+            unsafe {
+                Self::drop_values(c);
+                chunk = (*c.as_ptr()).prev;
+
+                let (layout, _) = Self::chunk_layout((*c.as_ptr()).capacity).unwrap();
+                let layout = NonZeroLayout::new(layout).unwrap();
+                self.allocator.deallocate(c.cast(), layout);
+            }
+        }
+    }
+}
+
+impl<T, A> TypedArena<T, A>
+where
+    A: Allocator,
+{
+    /// Move a value into the arena, returning a reference to it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc(&self, value: T) -> Result<&mut T, AllocError> {
+        let chunk = match self.chunk.get() {
+            Some(c) if unsafe { (*c.as_ptr()).len < (*c.as_ptr()).capacity } => c,
+            current => self.new_chunk(current)?,
+        };
+
+        unsafe {
+            let header = &mut *chunk.as_ptr();
+            let slot = Self::values(chunk).add(header.len);
+            slot.write(value);
+            header.len += 1;
+            Ok(&mut *slot)
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        self.try_alloc(value).expect("allocation failed")
+    }
+
+    fn new_chunk(
+        &self,
+        prev: Option<NonNull<ChunkHeader>>,
+    ) -> Result<NonNull<ChunkHeader>, AllocError> {
+        let capacity = match prev {
+            Some(c) => unsafe { (*c.as_ptr()).capacity }
+                .checked_mul(2)
+                .ok_or(AllocError)?,
+            None => cmp::max(1, INITIAL_CHUNK_BYTES / cmp::max(1, mem::size_of::<T>())),
+        };
+
+        let (layout, _) = Self::chunk_layout(capacity).ok_or(AllocError)?;
+        let layout = NonZeroLayout::new(layout).ok_or(AllocError)?;
+        let chunk = self.allocator.allocate(layout)?.cast::<ChunkHeader>();
+
+        unsafe {
+            chunk.as_ptr().write(ChunkHeader {
+                prev,
+                capacity,
+                len: 0,
+            })
+        };
+        self.chunk.set(Some(chunk));
+        Ok(chunk)
+    }
+}
+
+impl<T, A> Drop for TypedArena<T, A>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        unsafe { self.free_chunks(self.chunk.get()) };
+    }
+}
+
+impl<T, A> Debug for TypedArena<T, A>
+where
+    A: Deallocator + Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TypedArena")
+            .field("len", &self.len())
+            .field("allocator", &self.allocator)
+            .finish()
+    }
+}
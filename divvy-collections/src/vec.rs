@@ -0,0 +1,666 @@
+use core::{
+    cmp,
+    fmt::Debug,
+    hash::Hash,
+    iter::FusedIterator,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr, slice,
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator};
+
+use crate::{
+    boxed::Box,
+    growth::{Doubling, GrowthPolicy},
+    raw_vec::RawVec,
+};
+
+/// A contiguous growable array, with its memory obtained from an allocator.
+///
+/// Every operation that may allocate has a `try_` variant that reports allocation
+/// failure instead of panicking.
+pub struct Vec<T, A, P = Doubling>
+where
+    A: Deallocator,
+{
+    buf: RawVec<T, A, P>,
+    len: usize,
+}
+
+impl<T, A> Vec<T, A>
+where
+    A: Deallocator,
+{
+    pub const fn new_in(allocator: A) -> Self {
+        Self {
+            buf: RawVec::new_in(allocator),
+            len: 0,
+        }
+    }
+}
+
+impl<T, A> Vec<T, A>
+where
+    A: Allocator,
+{
+    pub fn try_with_capacity_in(capacity: usize, allocator: A) -> Result<Self, AllocError> {
+        Ok(Self {
+            buf: RawVec::try_with_capacity_in(capacity, allocator)?,
+            len: 0,
+        })
+    }
+
+    pub fn with_capacity_in(capacity: usize, allocator: A) -> Self {
+        Self::try_with_capacity_in(capacity, allocator).expect("allocation failed")
+    }
+
+    /// Create a vector of `n` clones of `elem`. Prefer the [`vec_in!`](crate::vec_in)
+    /// macro.
+    pub fn try_from_elem_in(elem: T, n: usize, allocator: A) -> Result<Self, AllocError>
+    where
+        T: Clone,
+    {
+        let mut v = Self::try_with_capacity_in(n, allocator)?;
+        if n > 0 {
+            for _ in 1..n {
+                unsafe { v.as_mut_ptr().add(v.len).write(elem.clone()) };
+                v.len += 1;
+            }
+            unsafe { v.as_mut_ptr().add(v.len).write(elem) };
+            v.len += 1;
+        }
+        Ok(v)
+    }
+
+    pub fn from_elem_in(elem: T, n: usize, allocator: A) -> Self
+    where
+        T: Clone,
+    {
+        Self::try_from_elem_in(elem, n, allocator).expect("allocation failed")
+    }
+}
+
+impl<T, A, P> Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    /// Create an empty vector that grows according to `policy`, without allocating.
+    pub const fn with_policy_in(policy: P, allocator: A) -> Self {
+        Self {
+            buf: RawVec::with_policy_in(policy, allocator),
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        self.buf.as_ptr()
+    }
+
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.buf.as_ptr()
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    /// Consume the vector without freeing its memory, returning a reference to its
+    /// elements. The allocator is leaked along with it.
+    pub fn leak<'a>(self) -> &'a mut [T]
+    where
+        A: 'a,
+    {
+        let mut this = ManuallyDrop::new(self);
+        unsafe { slice::from_raw_parts_mut(this.as_mut_ptr(), this.len) }
+    }
+
+    /// # Safety
+    ///
+    /// `new_len` must not exceed the capacity, and the first `new_len` elements must be
+    /// initialized.
+    #[inline]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        self.len = new_len;
+    }
+
+    /// Append an element if there is spare capacity, returning it back otherwise. Never
+    /// allocates.
+    #[inline]
+    pub fn push_within_capacity(&mut self, value: T) -> Result<(), T> {
+        if self.len == self.capacity() {
+            return Err(value);
+        }
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.as_ptr().add(self.len).read() })
+    }
+
+    /// Remove and return the element at `index`, shifting all later elements down.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "removal index out of bounds");
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let value = p.read();
+            ptr::copy(p.add(1), p, self.len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    /// Remove and return the element at `index`, replacing it with the last element.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "swap_remove index out of bounds");
+        unsafe {
+            let value = self.as_ptr().add(index).read();
+            let last = self.as_ptr().add(self.len - 1);
+            ptr::copy(last, self.as_mut_ptr().add(index), 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    /// Drop every element past `len`. Does nothing if the vector is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail =
+            ptr::slice_from_raw_parts_mut(unsafe { self.as_mut_ptr().add(len) }, self.len - len);
+        self.len = len;
+        unsafe { ptr::drop_in_place(tail) };
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T, A, P> Vec<T, A, P>
+where
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    pub fn try_with_capacity_and_policy_in(
+        capacity: usize,
+        policy: P,
+        allocator: A,
+    ) -> Result<Self, AllocError> {
+        Ok(Self {
+            buf: RawVec::try_with_capacity_and_policy_in(capacity, policy, allocator)?,
+            len: 0,
+        })
+    }
+
+    pub fn with_capacity_and_policy_in(capacity: usize, policy: P, allocator: A) -> Self {
+        Self::try_with_capacity_and_policy_in(capacity, policy, allocator)
+            .expect("allocation failed")
+    }
+
+    /// Ensure there is room for at least `additional` more elements.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.buf.try_reserve(self.len, additional)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional).expect("allocation failed")
+    }
+
+    /// Ensure there is room for at least `additional` more elements, without
+    /// deliberately over-allocating.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.buf.try_reserve_exact(self.len, additional)
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.try_reserve_exact(additional)
+            .expect("allocation failed")
+    }
+
+    /// Release any capacity beyond the current length.
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), AllocError> {
+        self.buf.try_shrink_to_fit(self.len)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.try_shrink_to_fit().expect("allocation failed")
+    }
+
+    /// Release any capacity beyond the current length or `min_capacity`, whichever is
+    /// larger.
+    pub fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), AllocError> {
+        self.buf.try_shrink_to_fit(cmp::max(self.len, min_capacity))
+    }
+
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.try_shrink_to(min_capacity).expect("allocation failed")
+    }
+
+    /// Release any excess capacity and convert the vector into a boxed slice. On
+    /// failure, the vector is returned unchanged.
+    pub fn try_into_boxed_slice(mut self) -> Result<Box<[T], A>, Self> {
+        if self.try_shrink_to_fit().is_err() {
+            return Err(self);
+        }
+        let len = self.len;
+        let this = ManuallyDrop::new(self);
+        let buf = unsafe { ptr::read(&this.buf) };
+        let (ptr, _, _, allocator) = buf.into_raw_parts();
+        let slice = ptr::slice_from_raw_parts_mut(ptr.as_ptr(), len);
+        Ok(unsafe { Box::from_raw_in(slice, allocator) })
+    }
+
+    pub fn into_boxed_slice(self) -> Box<[T], A> {
+        match self.try_into_boxed_slice() {
+            Ok(b) => b,
+            Err(_) => panic!("allocation failed"),
+        }
+    }
+
+    /// Append an element, growing the vector if needed. On failure, the element is
+    /// dropped and the vector is left unchanged.
+    #[inline]
+    pub fn try_push(&mut self, value: T) -> Result<(), AllocError> {
+        if self.len == self.capacity() {
+            self.buf.grow_amortized(self.len, 1)?;
+        }
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        self.try_push(value).expect("allocation failed")
+    }
+
+    /// Insert an element at `index`, shifting all later elements up. On failure, the
+    /// element is dropped and the vector is left unchanged.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the length.
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), AllocError> {
+        assert!(index <= self.len, "insertion index out of bounds");
+        self.try_reserve(1)?;
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            p.write(value);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.try_insert(index, value).expect("allocation failed")
+    }
+
+    /// Append every element of an iterator. On failure, the elements appended so far
+    /// are kept, and the rest of the iterator is not consumed.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), AllocError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+        self.try_reserve(iter.size_hint().0)?;
+        for value in iter {
+            self.try_push(value)?;
+        }
+        Ok(())
+    }
+
+    /// Append clones of every element of a slice. On failure, the vector is left
+    /// unchanged.
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), AllocError>
+    where
+        T: Clone,
+    {
+        self.try_reserve(other.len())?;
+        for value in other {
+            unsafe { self.as_mut_ptr().add(self.len).write(value.clone()) };
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.try_extend_from_slice(other)
+            .expect("allocation failed")
+    }
+
+    /// Clone the vector into a new allocation from a clone of its allocator.
+    pub fn try_clone(&self) -> Result<Self, AllocError>
+    where
+        T: Clone,
+        A: Clone,
+        P: Clone,
+    {
+        let mut v = Self::try_with_capacity_and_policy_in(
+            self.len,
+            self.buf.policy().clone(),
+            self.allocator().clone(),
+        )?;
+        v.try_extend_from_slice(self)?;
+        Ok(v)
+    }
+}
+
+impl<T, A, P> Deref for Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, A, P> DerefMut for Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A, P> Drop for Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) };
+    }
+}
+
+impl<T, A, P> Extend<T> for Vec<T, A, P>
+where
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter).expect("allocation failed")
+    }
+}
+
+impl<'a, T, A, P> Extend<&'a T> for Vec<T, A, P>
+where
+    T: Copy + 'a,
+    A: Allocator,
+    P: GrowthPolicy,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.try_extend(iter.into_iter().copied())
+            .expect("allocation failed")
+    }
+}
+
+impl<T, A, P> Clone for Vec<T, A, P>
+where
+    T: Clone,
+    A: Allocator + Clone,
+    P: GrowthPolicy + Clone,
+{
+    fn clone(&self) -> Self {
+        self.try_clone().expect("allocation failed")
+    }
+}
+
+impl<T, A, P> Debug for Vec<T, A, P>
+where
+    T: Debug,
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T, A, P> Hash for Vec<T, A, P>
+where
+    T: Hash,
+    A: Deallocator,
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T, U, A1, A2, P1, P2> PartialEq<Vec<U, A2, P2>> for Vec<T, A1, P1>
+where
+    T: PartialEq<U>,
+    A1: Deallocator,
+    A2: Deallocator,
+{
+    #[inline]
+    fn eq(&self, other: &Vec<U, A2, P2>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T, A, P> Eq for Vec<T, A, P>
+where
+    T: Eq,
+    A: Deallocator,
+{
+}
+
+impl<T, U, A, P> PartialEq<[U]> for Vec<T, A, P>
+where
+    T: PartialEq<U>,
+    A: Deallocator,
+{
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T, U, A, P, const N: usize> PartialEq<[U; N]> for Vec<T, A, P>
+where
+    T: PartialEq<U>,
+    A: Deallocator,
+{
+    #[inline]
+    fn eq(&self, other: &[U; N]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<'a, T, A, P> IntoIterator for &'a Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    type IntoIter = slice::Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A, P> IntoIterator for &'a mut Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    type IntoIter = slice::IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, A, P> IntoIterator for Vec<T, A, P>
+where
+    A: Deallocator,
+{
+    type IntoIter = IntoIter<T, A, P>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let this = ManuallyDrop::new(self);
+        let buf = unsafe { ptr::read(&this.buf) };
+        IntoIter {
+            buf,
+            start: 0,
+            end: this.len,
+        }
+    }
+}
+
+/// An owning iterator over the elements of a [`Vec`].
+pub struct IntoIter<T, A, P = Doubling>
+where
+    A: Deallocator,
+{
+    buf: RawVec<T, A, P>,
+    start: usize,
+    end: usize,
+}
+
+impl<T, A, P> IntoIter<T, A, P>
+where
+    A: Deallocator,
+{
+    /// Returns the elements that have not yet been yielded.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.end - self.start) }
+    }
+
+    fn as_ptr(&self) -> *mut T {
+        unsafe { self.buf.as_ptr().add(self.start) }
+    }
+}
+
+impl<T, A, P> Iterator for IntoIter<T, A, P>
+where
+    A: Deallocator,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        let value = unsafe { self.as_ptr().read() };
+        self.start += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, A, P> DoubleEndedIterator for IntoIter<T, A, P>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { self.buf.as_ptr().add(self.end).read() })
+    }
+}
+
+impl<T, A, P> ExactSizeIterator for IntoIter<T, A, P> where A: Deallocator {}
+
+impl<T, A, P> FusedIterator for IntoIter<T, A, P> where A: Deallocator {}
+
+impl<T, A, P> Drop for IntoIter<T, A, P>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        let rest = ptr::slice_from_raw_parts_mut(self.as_ptr(), self.end - self.start);
+        unsafe { ptr::drop_in_place(rest) };
+    }
+}
+
+impl<T, A, P> Debug for IntoIter<T, A, P>
+where
+    T: Debug,
+    A: Deallocator,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+/// Create a [`Vec`] from an allocator, like `vec!`.
+///
+/// ```ignore
+/// let zeros = vec_in![&arena; 0u8; 1024];
+/// let primes = vec_in![&arena; 2, 3, 5, 7];
+/// ```
+#[macro_export]
+macro_rules! vec_in {
+    ($allocator:expr $(;)?) => {
+        $crate::vec::Vec::new_in($allocator)
+    };
+    ($allocator:expr; $elem:expr; $n:expr) => {
+        $crate::vec::Vec::from_elem_in($elem, $n, $allocator)
+    };
+    ($allocator:expr; $($x:expr),+ $(,)?) => {{
+        let allocator = $allocator;
+        let items = [$($x),+];
+        let mut v = $crate::vec::Vec::with_capacity_in(items.len(), allocator);
+        ::core::iter::Extend::extend(&mut v, items);
+        v
+    }};
+}
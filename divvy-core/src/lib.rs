@@ -1,8 +1,17 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(unsafe_op_in_unsafe_fn)]
+#![cfg_attr(
+    feature = "strict_provenance",
+    feature(strict_provenance_lints),
+    deny(fuzzy_provenance_casts, lossy_provenance_casts)
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 use core::{
     alloc::Layout,
+    cmp,
     fmt::Display,
     num::NonZeroUsize,
     ptr::{self, NonNull},
@@ -42,6 +51,55 @@ impl NonZeroLayout {
         }
     }
 
+    /// Create a layout describing a value of type `T`, or `None` if `T` is zero sized.
+    pub fn of<T>() -> Option<Self> {
+        Self::new(Layout::new::<T>())
+    }
+
+    /// Create a layout describing an array of `n` values of type `T`, or `None` if the
+    /// array would be zero sized or its size would overflow.
+    pub fn array<T>(n: usize) -> Option<Self> {
+        Layout::array::<T>(n).ok().and_then(Self::new)
+    }
+
+    /// Create a layout from a size and alignment, or `None` if the size is zero or the
+    /// pair is not a valid [`Layout`].
+    pub fn from_size_align(size: usize, align: usize) -> Option<Self> {
+        Layout::from_size_align(size, align)
+            .ok()
+            .and_then(Self::new)
+    }
+
+    /// Create a layout describing `self` followed by `next`, including any padding
+    /// required to align `next`. Returns the combined layout and the offset of `next`
+    /// within it, or `None` on overflow.
+    ///
+    /// See [`Layout::extend`].
+    pub fn extend(&self, next: Layout) -> Option<(Self, usize)> {
+        let (layout, offset) = self.layout.extend(next).ok()?;
+        Some((Self { layout }, offset))
+    }
+
+    /// Round the size of this layout up to a multiple of its alignment.
+    ///
+    /// See [`Layout::pad_to_align`].
+    pub fn pad_to_align(&self) -> Self {
+        Self {
+            layout: self.layout.pad_to_align(),
+        }
+    }
+
+    /// Create a layout describing `n` consecutive instances of `self`, with padding
+    /// between each so that every instance is properly aligned. Returns the combined
+    /// layout and the stride between instances, or `None` if `n` is zero or the size
+    /// would overflow.
+    pub fn repeat(&self, n: usize) -> Option<(Self, usize)> {
+        let stride = self.pad_to_align().size();
+        let size = stride.checked_mul(n)?;
+        let this = Self::from_size_align(size, self.align())?;
+        Some((this, stride))
+    }
+
     pub fn nonzero_size(&self) -> NonZeroUsize {
         let size = self.layout.size();
         unsafe { NonZeroUsize::new_unchecked(size) }
@@ -67,7 +125,7 @@ pub trait Deallocator {
     ///
     /// # Safety
     /// - The pointer must be valid and the same as given by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The layout must be identical to that used when allocating the pointer.
     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout);
 
@@ -75,10 +133,10 @@ pub trait Deallocator {
     ///
     /// # Safety
     /// - The pointer must be valid and the same as returned by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The old layout must be identical to that used when allocating the pointer
     /// - The new layout must have a size and alignment such that new_size <= old_size
-    /// and new_align <= old_align.
+    ///   and new_align <= old_align.
     unsafe fn try_shrink(
         &self,
         ptr: NonNull<u8>,
@@ -91,6 +149,26 @@ pub trait Deallocator {
         Err(AllocError)
     }
 
+    /// Attempt to shrink a block of memory in-place, returning the actual usable size
+    /// of the block on success.
+    ///
+    /// The returned size is at least `new_layout.size()`, and may be used as the size
+    /// of the layout in later calls, as with
+    /// [allocate_at_least](Allocator::allocate_at_least).
+    ///
+    /// # Safety
+    /// See [try_shrink](Self::try_shrink).
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe { self.try_shrink(ptr, old_layout, new_layout)? };
+        Ok(new_layout.size())
+    }
+
     /// Creates a “by reference” adapter for this instance of `Dellocator`.
     /// The returned adapter also implements `Deallocator` and will simply borrow this.
     fn by_ref(&self) -> &Self
@@ -116,6 +194,20 @@ pub unsafe trait Allocator: Deallocator {
     /// Allocate a new block of memory that fits the provided layout.
     fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError>;
 
+    /// Allocate a new block of memory that fits the provided layout, returning the
+    /// actual usable size of the block alongside the pointer.
+    ///
+    /// The returned size is always at least `layout.size()`. Allocators that round
+    /// requests up to a coarser granularity (pages, size classes) should report the
+    /// rounded size, so that callers can make use of the slack. The full returned size
+    /// may be used as the size of the layout when deallocating, growing, or shrinking
+    /// the block.
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        let ptr = self.allocate(layout)?;
+        Ok((ptr, layout.size()))
+    }
+
     /// Allocate a new block of zeroed memory that fits the provided layout.
     #[inline]
     fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
@@ -124,6 +216,23 @@ pub unsafe trait Allocator: Deallocator {
         Ok(ptr)
     }
 
+    /// Allocate a new block of memory that fits the provided layout, with every byte
+    /// set to `byte`.
+    ///
+    /// The default implementation defers to [allocate_zeroed](Self::allocate_zeroed)
+    /// when `byte` is zero, so allocators that can obtain zeroed memory cheaply benefit
+    /// automatically.
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        if byte == 0 {
+            return self.allocate_zeroed(layout);
+        }
+
+        let ptr = self.allocate(layout)?;
+        unsafe { ptr.as_ptr().write_bytes(byte, layout.size()) };
+        Ok(ptr)
+    }
+
     /// Grow a previously allocated block of memory. If this call succeeds, the old
     /// pointer must not be used. If this call fails, the old pointer remains valid.
     ///
@@ -132,7 +241,7 @@ pub unsafe trait Allocator: Deallocator {
     ///
     /// # Safety
     /// - The pointer must be valid and the same as returned by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The old layout must be identical to that used when allocating the pointer
     /// - The new layout must have a size and alignment such that new_size >= old_size.
     unsafe fn grow(
@@ -154,6 +263,27 @@ pub unsafe trait Allocator: Deallocator {
         }
     }
 
+    /// Grow a previously allocated block of memory, returning the actual usable size of
+    /// the block alongside the pointer.
+    ///
+    /// The returned size is at least `new_layout.size()`, and may be used as the size
+    /// of the layout in later calls, as with
+    /// [allocate_at_least](Self::allocate_at_least). The default implementation defers
+    /// to [grow](Self::grow) and reports the requested size.
+    ///
+    /// # Safety
+    /// See [grow](Self::grow).
+    #[inline]
+    unsafe fn grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(NonNull<u8>, usize), AllocError> {
+        let ptr = unsafe { self.grow(ptr, old_layout, new_layout)? };
+        Ok((ptr, new_layout.size()))
+    }
+
     /// Grow a previously allocated block of memory, zeroing the newly allocated region.
     /// If this call succeeds, the old pointer must not be used. If this call fails, the
     /// old pointer remains valid.
@@ -163,7 +293,7 @@ pub unsafe trait Allocator: Deallocator {
     ///
     /// # Safety
     /// - The pointer must be valid and the same as returned by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The old layout must be identical to that used when allocating the pointer
     /// - The new layout must have a size and alignment such that new_size >= old_size.
     unsafe fn grow_zeroed(
@@ -181,7 +311,7 @@ pub unsafe trait Allocator: Deallocator {
             ptr::copy_nonoverlapping(ptr.as_ptr(), new.as_ptr(), old_layout.size());
             self.deallocate(ptr, old_layout);
 
-            ptr.as_ptr()
+            new.as_ptr()
                 .add(old_layout.size())
                 .write_bytes(0, new_layout.size() - old_layout.size());
 
@@ -197,7 +327,7 @@ pub unsafe trait Allocator: Deallocator {
     ///
     /// # Safety
     /// - The pointer must be valid and the same as returned by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The old layout must be identical to that used when allocating the pointer
     /// - The new layout must have a size and alignment such that new_size >= old_size.
     unsafe fn shrink(
@@ -219,11 +349,40 @@ pub unsafe trait Allocator: Deallocator {
         }
     }
 
+    /// Resize a previously allocated block of memory, growing or shrinking it as
+    /// needed. If this call succeeds, the old pointer must not be used. If this call
+    /// fails, the old pointer remains valid.
+    ///
+    /// This is a single entry point for `realloc`-style interfaces, and dispatches to
+    /// [grow](Self::grow) or [shrink](Self::shrink) depending on the new size. A block
+    /// whose size does not change is returned as is.
+    ///
+    /// # Safety
+    /// - The pointer must be valid and the same as returned by a previous call to
+    ///   `allocate`.
+    /// - The old layout must be identical to that used when allocating the pointer
+    /// - The new layout must have the same alignment as the old layout.
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            match old_layout.size().cmp(&new_layout.size()) {
+                cmp::Ordering::Less => self.grow(ptr, old_layout, new_layout),
+                cmp::Ordering::Equal => Ok(ptr),
+                cmp::Ordering::Greater => self.shrink(ptr, old_layout, new_layout),
+            }
+        }
+    }
+
     /// Attempt to grow a block of memory in-place.
     ///
     /// # Safety
     /// - The pointer must be valid and the same as returned by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The old layout must be identical to that used when allocating the pointer
     /// - The new layout must have a size and alignment such that new_size >= old_size.
     #[inline]
@@ -243,7 +402,7 @@ pub unsafe trait Allocator: Deallocator {
     ///
     /// # Safety
     /// - The pointer must be valid and the same as returned by a previous call to
-    /// `allocate`.
+    ///   `allocate`.
     /// - The old layout must be identical to that used when allocating the pointer
     /// - The new layout must have a size and alignment such that new_size >= old_size.
     #[inline]
@@ -262,89 +421,209 @@ pub unsafe trait Allocator: Deallocator {
         }
         Ok(())
     }
-}
-
-impl<'a, A> Deallocator for &'a A
-where
-    A: Deallocator + ?Sized,
-{
-    #[inline]
-    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
-        unsafe { (**self).deallocate(ptr, layout) }
-    }
 
+    /// Attempt to grow a block of memory in-place, returning the actual usable size of
+    /// the block on success.
+    ///
+    /// The returned size is at least `new_layout.size()`, and may be used as the size
+    /// of the layout in later calls, as with
+    /// [allocate_at_least](Self::allocate_at_least).
+    ///
+    /// # Safety
+    /// See [try_grow](Self::try_grow).
     #[inline]
-    unsafe fn try_shrink(
+    unsafe fn try_grow_at_least(
         &self,
         ptr: NonNull<u8>,
         old_layout: NonZeroLayout,
         new_layout: NonZeroLayout,
-    ) -> Result<(), AllocError> {
-        unsafe { (**self).try_shrink(ptr, old_layout, new_layout) }
+    ) -> Result<usize, AllocError> {
+        unsafe { self.try_grow(ptr, old_layout, new_layout)? };
+        Ok(new_layout.size())
     }
 }
 
-unsafe impl<'a, A> Allocator for &'a A
-where
-    A: Allocator + ?Sized,
-{
-    #[inline]
-    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
-        (**self).allocate(layout)
-    }
+/// An allocator that can determine whether a block of memory belongs to it.
+///
+/// This is primarily useful for composing allocators, where a deallocation must be
+/// routed back to the allocator that originally produced the block.
+pub trait Owns {
+    /// Returns `true` if the block described by `ptr` and `layout` lies within memory
+    /// managed by this allocator.
+    ///
+    /// A return value of `true` does not imply that the block is currently allocated,
+    /// only that it would be valid for this allocator to have returned it.
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool;
+}
 
-    #[inline]
-    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
-        (**self).allocate_zeroed(layout)
-    }
+/// Implement the allocator traits for a pointer to an allocator, forwarding every
+/// method to the allocator it points to.
+macro_rules! forward_impls {
+    ($($(#[$attr:meta])* $ty:ty),* $(,)?) => {$(
+        $(#[$attr])*
+        impl<A> Deallocator for $ty
+        where
+            A: Deallocator + ?Sized,
+        {
+            #[inline]
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+                unsafe { (**self).deallocate(ptr, layout) }
+            }
 
-    #[inline]
-    unsafe fn grow(
-        &self,
-        ptr: NonNull<u8>,
-        old_layout: NonZeroLayout,
-        new_layout: NonZeroLayout,
-    ) -> Result<NonNull<u8>, AllocError> {
-        unsafe { (**self).grow(ptr, old_layout, new_layout) }
-    }
+            #[inline]
+            unsafe fn try_shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<(), AllocError> {
+                unsafe { (**self).try_shrink(ptr, old_layout, new_layout) }
+            }
 
-    #[inline]
-    unsafe fn grow_zeroed(
-        &self,
-        ptr: NonNull<u8>,
-        old_layout: NonZeroLayout,
-        new_layout: NonZeroLayout,
-    ) -> Result<NonNull<u8>, AllocError> {
-        unsafe { (**self).grow_zeroed(ptr, old_layout, new_layout) }
-    }
+            #[inline]
+            unsafe fn try_shrink_at_least(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<usize, AllocError> {
+                unsafe { (**self).try_shrink_at_least(ptr, old_layout, new_layout) }
+            }
+        }
 
-    #[inline]
-    unsafe fn shrink(
-        &self,
-        ptr: NonNull<u8>,
-        old_layout: NonZeroLayout,
-        new_layout: NonZeroLayout,
-    ) -> Result<NonNull<u8>, AllocError> {
-        unsafe { (**self).shrink(ptr, old_layout, new_layout) }
-    }
+        $(#[$attr])*
+        unsafe impl<A> Allocator for $ty
+        where
+            A: Allocator + ?Sized,
+        {
+            #[inline]
+            fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+                (**self).allocate(layout)
+            }
 
-    #[inline]
-    unsafe fn try_grow(
-        &self,
-        ptr: NonNull<u8>,
-        old_layout: NonZeroLayout,
-        new_layout: NonZeroLayout,
-    ) -> Result<(), AllocError> {
-        unsafe { (**self).try_grow(ptr, old_layout, new_layout) }
-    }
+            #[inline]
+            fn allocate_at_least(
+                &self,
+                layout: NonZeroLayout,
+            ) -> Result<(NonNull<u8>, usize), AllocError> {
+                (**self).allocate_at_least(layout)
+            }
 
-    #[inline]
-    unsafe fn try_grow_zeroed(
-        &self,
-        ptr: NonNull<u8>,
-        old_layout: NonZeroLayout,
-        new_layout: NonZeroLayout,
-    ) -> Result<(), AllocError> {
-        unsafe { (**self).try_grow_zeroed(ptr, old_layout, new_layout) }
-    }
+            #[inline]
+            fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+                (**self).allocate_zeroed(layout)
+            }
+
+            #[inline]
+            fn allocate_filled(
+                &self,
+                layout: NonZeroLayout,
+                byte: u8,
+            ) -> Result<NonNull<u8>, AllocError> {
+                (**self).allocate_filled(layout, byte)
+            }
+
+            #[inline]
+            unsafe fn grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<NonNull<u8>, AllocError> {
+                unsafe { (**self).grow(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn grow_at_least(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<(NonNull<u8>, usize), AllocError> {
+                unsafe { (**self).grow_at_least(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn grow_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<NonNull<u8>, AllocError> {
+                unsafe { (**self).grow_zeroed(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<NonNull<u8>, AllocError> {
+                unsafe { (**self).shrink(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn reallocate(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<NonNull<u8>, AllocError> {
+                unsafe { (**self).reallocate(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn try_grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<(), AllocError> {
+                unsafe { (**self).try_grow(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn try_grow_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<(), AllocError> {
+                unsafe { (**self).try_grow_zeroed(ptr, old_layout, new_layout) }
+            }
+
+            #[inline]
+            unsafe fn try_grow_at_least(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: NonZeroLayout,
+                new_layout: NonZeroLayout,
+            ) -> Result<usize, AllocError> {
+                unsafe { (**self).try_grow_at_least(ptr, old_layout, new_layout) }
+            }
+        }
+
+        $(#[$attr])*
+        impl<A> Owns for $ty
+        where
+            A: Owns + ?Sized,
+        {
+            #[inline]
+            fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+                (**self).owns(ptr, layout)
+            }
+        }
+    )*};
+}
+
+forward_impls! {
+    &A,
+    &mut A,
+    #[cfg(feature = "alloc")]
+    alloc::boxed::Box<A>,
+    #[cfg(feature = "alloc")]
+    alloc::rc::Rc<A>,
+    #[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+    alloc::sync::Arc<A>,
 }
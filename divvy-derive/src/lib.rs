@@ -0,0 +1,603 @@
+//! Derive macros implementing the divvy allocator traits by delegating to a field.
+//!
+//! Allocator wrappers usually intercept one or two operations and forward the rest to the
+//! allocator they wrap. The derives here generate the forwarding impls, so that only the
+//! interesting parts need to be written by hand.
+//!
+//! ```ignore
+//! #[derive(Deallocator, Allocator, Owns)]
+//! #[divvy(delegate = "inner")]
+//! struct Named<A> {
+//!     inner: A,
+//!     name: &'static str,
+//! }
+//! ```
+//!
+//! The delegate may be omitted for structs with a single field. Every generated impl
+//! requires the delegate's type to implement the same trait. The traits are referred to
+//! through `::divvy` by default; other paths, such as `::divvy_core` for crates that do
+//! not depend on the facade, can be given with `#[divvy(crate = "divvy_core")]`.
+
+use std::fmt::Write;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Implement `Deallocator` by forwarding to a field.
+#[proc_macro_derive(Deallocator, attributes(divvy))]
+pub fn derive_deallocator(input: TokenStream) -> TokenStream {
+    expand(input, Trait::Deallocator)
+}
+
+/// Implement `Allocator` by forwarding to a field.
+///
+/// `Allocator` is an unsafe trait. The derived impl upholds its contract as long as the
+/// delegate does, since it hands out exactly the blocks that the delegate returns.
+#[proc_macro_derive(Allocator, attributes(divvy))]
+pub fn derive_allocator(input: TokenStream) -> TokenStream {
+    expand(input, Trait::Allocator)
+}
+
+/// Implement `Owns` by forwarding to a field.
+#[proc_macro_derive(Owns, attributes(divvy))]
+pub fn derive_owns(input: TokenStream) -> TokenStream {
+    expand(input, Trait::Owns)
+}
+
+/// Implement `Trim` by forwarding to a field. `Trim` is defined by `divvy` itself, so this
+/// cannot be used with `#[divvy(crate = "divvy_core")]`.
+#[proc_macro_derive(Trim, attributes(divvy))]
+pub fn derive_trim(input: TokenStream) -> TokenStream {
+    expand(input, Trait::Trim)
+}
+
+#[derive(Clone, Copy)]
+enum Trait {
+    Deallocator,
+    Allocator,
+    Owns,
+    Trim,
+}
+
+impl Trait {
+    fn name(self) -> &'static str {
+        match self {
+            Trait::Deallocator => "Deallocator",
+            Trait::Allocator => "Allocator",
+            Trait::Owns => "Owns",
+            Trait::Trim => "Trim",
+        }
+    }
+
+    /// The bodies of the forwarding methods, with `$k` standing for the crate path and
+    /// `$f` for the delegate field.
+    fn methods(self) -> &'static str {
+        match self {
+            Trait::Deallocator => DEALLOCATOR_METHODS,
+            Trait::Allocator => ALLOCATOR_METHODS,
+            Trait::Owns => OWNS_METHODS,
+            Trait::Trim => TRIM_METHODS,
+        }
+    }
+}
+
+const DEALLOCATOR_METHODS: &str = "
+    #[inline]
+    unsafe fn deallocate(&self, ptr: ::core::ptr::NonNull<u8>, layout: $k::NonZeroLayout) {
+        unsafe { $k::Deallocator::deallocate(&self.$f, ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<(), $k::AllocError> {
+        unsafe { $k::Deallocator::try_shrink(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<usize, $k::AllocError> {
+        unsafe { $k::Deallocator::try_shrink_at_least(&self.$f, ptr, old_layout, new_layout) }
+    }
+";
+
+const ALLOCATOR_METHODS: &str = "
+    #[inline]
+    fn allocate(
+        &self,
+        layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        $k::Allocator::allocate(&self.$f, layout)
+    }
+
+    #[inline]
+    fn allocate_at_least(
+        &self,
+        layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<(::core::ptr::NonNull<u8>, usize), $k::AllocError> {
+        $k::Allocator::allocate_at_least(&self.$f, layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(
+        &self,
+        layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        $k::Allocator::allocate_zeroed(&self.$f, layout)
+    }
+
+    #[inline]
+    fn allocate_filled(
+        &self,
+        layout: $k::NonZeroLayout,
+        byte: u8,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        $k::Allocator::allocate_filled(&self.$f, layout, byte)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        unsafe { $k::Allocator::grow(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_at_least(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<(::core::ptr::NonNull<u8>, usize), $k::AllocError> {
+        unsafe { $k::Allocator::grow_at_least(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        unsafe { $k::Allocator::grow_zeroed(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        unsafe { $k::Allocator::shrink(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<::core::ptr::NonNull<u8>, $k::AllocError> {
+        unsafe { $k::Allocator::reallocate(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<(), $k::AllocError> {
+        unsafe { $k::Allocator::try_grow(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<(), $k::AllocError> {
+        unsafe { $k::Allocator::try_grow_zeroed(&self.$f, ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_at_least(
+        &self,
+        ptr: ::core::ptr::NonNull<u8>,
+        old_layout: $k::NonZeroLayout,
+        new_layout: $k::NonZeroLayout,
+    ) -> ::core::result::Result<usize, $k::AllocError> {
+        unsafe { $k::Allocator::try_grow_at_least(&self.$f, ptr, old_layout, new_layout) }
+    }
+";
+
+const OWNS_METHODS: &str = "
+    #[inline]
+    fn owns(&self, ptr: ::core::ptr::NonNull<u8>, layout: $k::NonZeroLayout) -> bool {
+        $k::Owns::owns(&self.$f, ptr, layout)
+    }
+";
+
+const TRIM_METHODS: &str = "
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        $k::Trim::trim(&self.$f, pad)
+    }
+";
+
+fn expand(input: TokenStream, t: Trait) -> TokenStream {
+    match Input::parse(input).and_then(|input| input.expand(t)) {
+        Ok(tokens) => tokens,
+        Err((span, message)) => compile_error(span, &message),
+    }
+}
+
+type Error = (Span, String);
+
+struct Input {
+    name: Ident,
+    /// The generic parameters, without the surrounding angle brackets.
+    generics: Vec<TokenTree>,
+    /// The where clause predicates, without the `where` keyword.
+    predicates: Vec<TokenTree>,
+    fields: Vec<Field>,
+    delegate: Option<(String, Span)>,
+    krate: String,
+}
+
+struct Field {
+    /// The field's name, or its index in a tuple struct.
+    name: String,
+    ty: Vec<TokenTree>,
+}
+
+impl Input {
+    fn parse(input: TokenStream) -> Result<Self, Error> {
+        let mut tokens = input.into_iter().peekable();
+        let mut delegate = None;
+        let mut krate = String::from("::divvy");
+
+        // Outer attributes and visibility.
+        loop {
+            match tokens.peek() {
+                Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                    tokens.next();
+                    if let Some(TokenTree::Group(g)) = tokens.next() {
+                        parse_attribute(g, &mut delegate, &mut krate)?;
+                    }
+                }
+                Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                    tokens.next();
+                    if let Some(TokenTree::Group(g)) = tokens.peek() {
+                        if g.delimiter() == Delimiter::Parenthesis {
+                            tokens.next();
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        match tokens.next() {
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => {}
+            Some(tt) => {
+                return Err((
+                    tt.span(),
+                    "this trait can only be derived for structs".into(),
+                ))
+            }
+            None => return Err((Span::call_site(), "expected a struct".into())),
+        }
+        let name = match tokens.next() {
+            Some(TokenTree::Ident(i)) => i,
+            _ => return Err((Span::call_site(), "expected the struct's name".into())),
+        };
+
+        let mut generics = Vec::new();
+        if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+            tokens.next();
+            let mut depth = 1;
+            let mut arrow = false;
+            for tt in tokens.by_ref() {
+                if let TokenTree::Punct(p) = &tt {
+                    match p.as_char() {
+                        '<' => depth += 1,
+                        '>' if !arrow => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+                } else {
+                    arrow = false;
+                }
+                generics.push(tt);
+            }
+        }
+
+        let mut predicates = Vec::new();
+        let mut fields = None;
+        let mut in_where = false;
+        for tt in tokens {
+            match &tt {
+                TokenTree::Ident(i) if i.to_string() == "where" => in_where = true,
+                TokenTree::Group(g) if g.delimiter() == Delimiter::Brace && fields.is_none() => {
+                    fields = Some(parse_fields(g.stream(), true)?);
+                    in_where = false;
+                }
+                TokenTree::Group(g)
+                    if g.delimiter() == Delimiter::Parenthesis && fields.is_none() && !in_where =>
+                {
+                    fields = Some(parse_fields(g.stream(), false)?);
+                }
+                TokenTree::Punct(p) if p.as_char() == ';' && !in_where => {}
+                TokenTree::Punct(p) if p.as_char() == ';' => in_where = false,
+                _ if in_where => predicates.push(tt),
+                _ => {}
+            }
+        }
+
+        let Some(fields) = fields else {
+            return Err((
+                name.span(),
+                "unit structs have no field to delegate to".into(),
+            ));
+        };
+
+        Ok(Self {
+            name,
+            generics,
+            predicates,
+            fields,
+            delegate,
+            krate,
+        })
+    }
+
+    fn expand(&self, t: Trait) -> Result<TokenStream, Error> {
+        let field = match &self.delegate {
+            Some((name, span)) => self
+                .fields
+                .iter()
+                .find(|f| &f.name == name)
+                .ok_or_else(|| (*span, format!("no field named `{name}`")))?,
+            None if self.fields.len() == 1 => &self.fields[0],
+            None => {
+                return Err((
+                    self.name.span(),
+                    "structs with several fields need `#[divvy(delegate = \"field\")]`".into(),
+                ))
+            }
+        };
+
+        let params = split_commas(&self.generics);
+        let mut impl_generics = String::new();
+        let mut type_generics = String::new();
+        for param in &params {
+            let (decl, name) = generic_param(param);
+            write!(impl_generics, "{decl}, ").unwrap();
+            write!(type_generics, "{name}, ").unwrap();
+        }
+
+        let mut predicates = stream(&self.predicates).to_string();
+        if !predicates.is_empty() && !predicates.trim_end().ends_with(',') {
+            predicates.push(',');
+        }
+        let ty = stream(&field.ty);
+        let k = &self.krate;
+        let trait_name = t.name();
+        write!(predicates, " {ty}: {k}::{trait_name}").unwrap();
+
+        let unsafety = if matches!(t, Trait::Allocator) {
+            "unsafe "
+        } else {
+            ""
+        };
+        let methods = t.methods().replace("$k", k).replace("$f", &field.name);
+        let name = &self.name;
+        let code = format!(
+            "#[automatically_derived] {unsafety}impl<{impl_generics}> {k}::{trait_name} for \
+             {name}<{type_generics}> where {predicates} {{ {methods} }}"
+        );
+        code.parse()
+            .map_err(|_| (self.name.span(), "failed to generate impl".into()))
+    }
+}
+
+fn parse_attribute(
+    attr: Group,
+    delegate: &mut Option<(String, Span)>,
+    krate: &mut String,
+) -> Result<(), Error> {
+    let mut tokens = attr.stream().into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "divvy" => {}
+        _ => return Ok(()),
+    }
+    let Some(TokenTree::Group(args)) = tokens.next() else {
+        return Err((attr.span(), "expected `#[divvy(...)]`".into()));
+    };
+
+    for arg in split_commas(&args.stream().into_iter().collect::<Vec<_>>()) {
+        let (key, value) = match arg.as_slice() {
+            [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Literal(value)]
+                if eq.as_char() == '=' =>
+            {
+                (key, value)
+            }
+            _ => {
+                let span = arg.first().map_or(args.span(), TokenTree::span);
+                return Err((span, "expected `key = \"value\"`".into()));
+            }
+        };
+        let text = string_literal(value)?;
+        match key.to_string().as_str() {
+            "delegate" => *delegate = Some((text, value.span())),
+            "crate" => *krate = text,
+            other => return Err((key.span(), format!("unknown divvy attribute `{other}`"))),
+        }
+    }
+    Ok(())
+}
+
+fn parse_fields(body: TokenStream, named: bool) -> Result<Vec<Field>, Error> {
+    let tokens: Vec<_> = body.into_iter().collect();
+    let mut fields = Vec::new();
+
+    for (index, field) in split_commas(&tokens).into_iter().enumerate() {
+        let mut rest = field.as_slice();
+        loop {
+            match rest {
+                [TokenTree::Punct(p), TokenTree::Group(_), tail @ ..] if p.as_char() == '#' => {
+                    rest = tail
+                }
+                [TokenTree::Ident(i), TokenTree::Group(g), tail @ ..]
+                    if i.to_string() == "pub" && g.delimiter() == Delimiter::Parenthesis =>
+                {
+                    rest = tail
+                }
+                [TokenTree::Ident(i), tail @ ..] if i.to_string() == "pub" => rest = tail,
+                _ => break,
+            }
+        }
+
+        if named {
+            match rest {
+                [TokenTree::Ident(name), TokenTree::Punct(colon), ty @ ..]
+                    if colon.as_char() == ':' =>
+                {
+                    fields.push(Field {
+                        name: name.to_string(),
+                        ty: ty.to_vec(),
+                    });
+                }
+                _ => {
+                    let span = rest.first().map_or(Span::call_site(), TokenTree::span);
+                    return Err((span, "expected a field".into()));
+                }
+            }
+        } else {
+            fields.push(Field {
+                name: index.to_string(),
+                ty: rest.to_vec(),
+            });
+        }
+    }
+    Ok(fields)
+}
+
+/// Split tokens at commas outside of angle brackets, dropping empty segments.
+fn split_commas(tokens: &[TokenTree]) -> Vec<Vec<TokenTree>> {
+    let mut segments = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0usize;
+    let mut arrow = false;
+
+    for tt in tokens {
+        if let TokenTree::Punct(p) = tt {
+            match p.as_char() {
+                ',' if depth == 0 => {
+                    segments.push(std::mem::take(&mut current));
+                    arrow = false;
+                    continue;
+                }
+                '<' => depth += 1,
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        current.push(tt.clone());
+    }
+    segments.push(current);
+    segments.retain(|s| !s.is_empty());
+    segments
+}
+
+/// Returns a generic parameter as declared on the impl, without any default, and the
+/// name used to refer to it in the type.
+fn generic_param(param: &[TokenTree]) -> (TokenStream, TokenStream) {
+    let mut depth = 0usize;
+    let end = param
+        .iter()
+        .position(|tt| match tt {
+            TokenTree::Punct(p) => match p.as_char() {
+                '<' => {
+                    depth += 1;
+                    false
+                }
+                '>' => {
+                    depth = depth.saturating_sub(1);
+                    false
+                }
+                '=' => depth == 0,
+                _ => false,
+            },
+            _ => false,
+        })
+        .unwrap_or(param.len());
+    let decl = stream(&param[..end]);
+
+    let name = match param {
+        [TokenTree::Punct(p), lifetime @ TokenTree::Ident(_), ..] if p.as_char() == '\'' => {
+            stream(&[TokenTree::Punct(p.clone()), lifetime.clone()])
+        }
+        [TokenTree::Ident(c), name @ TokenTree::Ident(_), ..] if c.to_string() == "const" => {
+            stream(std::slice::from_ref(name))
+        }
+        [name, ..] => stream(std::slice::from_ref(name)),
+        [] => TokenStream::new(),
+    };
+    (decl, name)
+}
+
+fn string_literal(literal: &Literal) -> Result<String, Error> {
+    let text = literal.to_string();
+    text.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .map(str::to_owned)
+        .ok_or_else(|| (literal.span(), "expected a string literal".into()))
+}
+
+fn stream(tokens: &[TokenTree]) -> TokenStream {
+    tokens.iter().cloned().collect()
+}
+
+fn compile_error(span: Span, message: &str) -> TokenStream {
+    let mut literal = Literal::string(message);
+    literal.set_span(span);
+    let mut group = Group::new(Delimiter::Brace, TokenTree::Literal(literal).into());
+    group.set_span(span);
+
+    let mut path = Vec::new();
+    for name in ["core", "compile_error"] {
+        let mut colon = Punct::new(':', Spacing::Joint);
+        colon.set_span(span);
+        path.push(TokenTree::Punct(colon));
+        let mut colon = Punct::new(':', Spacing::Alone);
+        colon.set_span(span);
+        path.push(TokenTree::Punct(colon));
+        path.push(TokenTree::Ident(Ident::new(name, span)));
+    }
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+    path.push(TokenTree::Punct(bang));
+    path.push(TokenTree::Group(group));
+    path.into_iter().collect()
+}
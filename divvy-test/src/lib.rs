@@ -0,0 +1,290 @@
+//! A reusable conformance suite for [Allocator] implementations.
+//!
+//! Every check exercises the allocator through its public interface and panics with a
+//! description of the violated guarantee, which makes the suite suitable for running
+//! directly inside a `#[test]`:
+//!
+//! ```ignore
+//! #[test]
+//! fn conformance() {
+//!     divvy_test::check(&MyAllocator::new());
+//! }
+//! ```
+//!
+//! Allocation failures are not considered violations, so bounded allocators can be
+//! checked as well; requests that fail are simply skipped.
+//!
+//! For testing how code interacts with its allocator, rather than the allocator itself,
+//! see [MockAlloc]. To benchmark allocators against a real workload, record it with a
+//! [Recorder] and [replay] the resulting [Trace].
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::ptr::NonNull;
+
+use divvy_core::{Allocator, NonZeroLayout};
+
+pub use crate::{
+    mock::{Action, Call, MockAlloc, Op},
+    rng::Rng,
+    stress::{AlignDist, SizeDist, Stress, StressReport, Weights},
+    trace::{replay, Event, Recorder, ReplayReport, Trace, TraceError, TraceOp},
+};
+
+mod mock;
+mod rng;
+mod stress;
+mod trace;
+
+/// Parameters for the randomized parts of the suite.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The number of operations performed by [check_random].
+    pub iterations: usize,
+    /// The maximum number of blocks [check_random] keeps alive at once.
+    pub max_live: usize,
+    /// The largest block size requested.
+    pub max_size: usize,
+    /// The largest alignment requested. Must be a power of two.
+    pub max_align: usize,
+    /// The seed for the pseudo-random operation sequence.
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            max_live: 64,
+            max_size: 4096,
+            max_align: 256,
+            seed: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+}
+
+/// Run the whole suite against `allocator` with the default [Config].
+#[track_caller]
+pub fn check<A: Allocator>(allocator: &A) {
+    check_with(allocator, &Config::default());
+}
+
+/// Run the whole suite against `allocator`.
+#[track_caller]
+pub fn check_with<A: Allocator>(allocator: &A, config: &Config) {
+    check_alignment(allocator, config);
+    check_zeroed(allocator, config);
+    check_filled(allocator, config);
+    check_at_least(allocator, config);
+    check_grow(allocator, config);
+    check_shrink(allocator, config);
+    check_random(allocator, config);
+}
+
+/// Check that blocks of every size and alignment up to the configured maximums are
+/// suitably aligned and writable.
+#[track_caller]
+pub fn check_alignment<A: Allocator>(allocator: &A, config: &Config) {
+    for layout in layouts(config) {
+        let Ok(ptr) = allocator.allocate(layout) else {
+            continue;
+        };
+        assert_aligned(ptr, layout);
+        unsafe {
+            fill(ptr, layout.size(), 0xa5);
+            assert_filled(ptr, 0, layout.size(), 0xa5, "allocate");
+            allocator.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Check that `allocate_zeroed` returns zeroed memory, including memory that was
+/// previously dirtied and freed.
+#[track_caller]
+pub fn check_zeroed<A: Allocator>(allocator: &A, config: &Config) {
+    for layout in layouts(config) {
+        if let Ok(ptr) = allocator.allocate(layout) {
+            unsafe {
+                fill(ptr, layout.size(), 0xff);
+                allocator.deallocate(ptr, layout);
+            }
+        }
+
+        let Ok(ptr) = allocator.allocate_zeroed(layout) else {
+            continue;
+        };
+        assert_aligned(ptr, layout);
+        unsafe {
+            assert_filled(ptr, 0, layout.size(), 0, "allocate_zeroed");
+            allocator.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Check that `allocate_filled` sets every byte of the block.
+#[track_caller]
+pub fn check_filled<A: Allocator>(allocator: &A, config: &Config) {
+    for (i, layout) in layouts(config).enumerate() {
+        let byte = i as u8;
+        let Ok(ptr) = allocator.allocate_filled(layout, byte) else {
+            continue;
+        };
+        assert_aligned(ptr, layout);
+        unsafe {
+            assert_filled(ptr, 0, layout.size(), byte, "allocate_filled");
+            allocator.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Check that `allocate_at_least` and `grow_at_least` report a usable size of at least
+/// the requested size, and that the whole reported size can be written and used in later
+/// calls.
+#[track_caller]
+pub fn check_at_least<A: Allocator>(allocator: &A, config: &Config) {
+    for layout in layouts(config) {
+        let Ok((ptr, size)) = allocator.allocate_at_least(layout) else {
+            continue;
+        };
+        assert_aligned(ptr, layout);
+        let actual = usable_layout(layout, size, "allocate_at_least");
+        unsafe {
+            fill(ptr, size, 0x5a);
+            assert_filled(ptr, 0, size, 0x5a, "allocate_at_least");
+
+            let Some(new) = NonZeroLayout::from_size_align(size * 2, layout.align()) else {
+                allocator.deallocate(ptr, actual);
+                continue;
+            };
+            let Ok((ptr, new_size)) = allocator.grow_at_least(ptr, actual, new) else {
+                assert_filled(ptr, 0, size, 0x5a, "failed grow_at_least");
+                allocator.deallocate(ptr, actual);
+                continue;
+            };
+            assert_aligned(ptr, new);
+            let grown = usable_layout(new, new_size, "grow_at_least");
+            assert_filled(ptr, 0, size, 0x5a, "grow_at_least");
+            fill(ptr, new_size, 0xa5);
+            allocator.deallocate(ptr, grown);
+        }
+    }
+}
+
+/// Check that `grow` and `grow_zeroed` preserve the contents of the block, and that
+/// `grow_zeroed` zeroes the new tail.
+#[track_caller]
+pub fn check_grow<A: Allocator>(allocator: &A, config: &Config) {
+    for old in layouts(config) {
+        let Some(new) = NonZeroLayout::from_size_align(old.size() * 2, old.align()) else {
+            continue;
+        };
+
+        for zeroed in [false, true] {
+            let Ok(ptr) = allocator.allocate(old) else {
+                continue;
+            };
+            unsafe {
+                fill(ptr, old.size(), 0x3c);
+                let result = if zeroed {
+                    allocator.grow_zeroed(ptr, old, new)
+                } else {
+                    allocator.grow(ptr, old, new)
+                };
+                let Ok(ptr) = result else {
+                    assert_filled(ptr, 0, old.size(), 0x3c, "failed grow");
+                    allocator.deallocate(ptr, old);
+                    continue;
+                };
+                assert_aligned(ptr, new);
+                assert_filled(ptr, 0, old.size(), 0x3c, "grow");
+                if zeroed {
+                    assert_filled(ptr, old.size(), new.size(), 0, "grow_zeroed");
+                }
+                fill(ptr, new.size(), 0xc3);
+                allocator.deallocate(ptr, new);
+            }
+        }
+    }
+}
+
+/// Check that `shrink` preserves the retained prefix of the block.
+#[track_caller]
+pub fn check_shrink<A: Allocator>(allocator: &A, config: &Config) {
+    for old in layouts(config) {
+        let Some(new) = NonZeroLayout::from_size_align(old.size().div_ceil(2), old.align()) else {
+            continue;
+        };
+        let Ok(ptr) = allocator.allocate(old) else {
+            continue;
+        };
+        unsafe {
+            fill(ptr, old.size(), 0x69);
+            let Ok(ptr) = allocator.shrink(ptr, old, new) else {
+                assert_filled(ptr, 0, old.size(), 0x69, "failed shrink");
+                allocator.deallocate(ptr, old);
+                continue;
+            };
+            assert_aligned(ptr, new);
+            assert_filled(ptr, 0, new.size(), 0x69, "shrink");
+            allocator.deallocate(ptr, new);
+        }
+    }
+}
+
+/// Run a pseudo-random sequence of operations, checking after every step that live
+/// blocks are aligned, do not overlap, and retain their contents. See [Stress] for more
+/// control over the sequence.
+#[track_caller]
+pub fn check_random<A: Allocator>(allocator: &A, config: &Config) {
+    Stress::from(config).run(allocator);
+}
+
+fn layouts(config: &Config) -> impl Iterator<Item = NonZeroLayout> + '_ {
+    let aligns = (0..usize::BITS)
+        .map(|shift| 1usize << shift)
+        .take_while(|&align| align <= config.max_align);
+
+    aligns.flat_map(|align| {
+        let sizes = [1, align / 2, align - 1, align, align + 1, align * 3, 4096];
+        sizes
+            .into_iter()
+            .filter(|&size| size != 0 && size <= config.max_size)
+            .filter_map(move |size| NonZeroLayout::from_size_align(size, align))
+    })
+}
+
+/// Returns the layout of a block whose usable size was reported as `size`, after checking
+/// that the size covers the requested layout.
+#[track_caller]
+fn usable_layout(requested: NonZeroLayout, size: usize, op: &str) -> NonZeroLayout {
+    assert!(
+        size >= requested.size(),
+        "{op} returned {size} bytes for a request of {} bytes",
+        requested.size()
+    );
+    NonZeroLayout::from_size_align(size, requested.align())
+        .unwrap_or_else(|| panic!("{op} returned an invalid size"))
+}
+
+#[track_caller]
+fn assert_aligned(ptr: NonNull<u8>, layout: NonZeroLayout) {
+    assert!(
+        ptr.as_ptr().addr().is_multiple_of(layout.align()),
+        "block {ptr:p} is not aligned to {}",
+        layout.align()
+    );
+}
+
+unsafe fn fill(ptr: NonNull<u8>, size: usize, byte: u8) {
+    unsafe { ptr.as_ptr().write_bytes(byte, size) };
+}
+
+#[track_caller]
+unsafe fn assert_filled(ptr: NonNull<u8>, start: usize, end: usize, byte: u8, what: &str) {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), end) };
+    if let Some(offset) = (start..end).find(|&i| bytes[i] != byte) {
+        panic!(
+            "{what}: byte {offset} of block {ptr:p} is {:#04x}, expected {byte:#04x}",
+            bytes[offset]
+        );
+    }
+}
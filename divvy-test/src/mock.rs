@@ -0,0 +1,423 @@
+use std::{
+    boxed::Box,
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::{self, Debug},
+    ptr::NonNull,
+    vec::Vec,
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+/// The kind of operation a [MockAlloc] was asked to perform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Allocate,
+    AllocateAtLeast,
+    AllocateZeroed,
+    AllocateFilled,
+    Grow,
+    GrowZeroed,
+    Shrink,
+    TryGrow,
+    TryGrowZeroed,
+    TryShrink,
+    Deallocate,
+}
+
+/// A single call made to a [MockAlloc].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Call {
+    pub op: Op,
+    /// The requested layout, or the new layout when resizing.
+    pub layout: NonZeroLayout,
+    /// The previous layout when resizing or deallocating.
+    pub old_layout: Option<NonZeroLayout>,
+    /// The existing block when resizing or deallocating.
+    pub ptr: Option<NonNull<u8>>,
+}
+
+/// What a [MockAlloc] should do in response to a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Forward the call to the backing allocator.
+    Forward,
+    /// Fail the call with [AllocError].
+    Fail,
+    /// Return the given address without touching the backing allocator. Only valid for
+    /// calls that return a pointer. Blocks returned this way are never forwarded to the
+    /// backing allocator when deallocated. Resizing a backing block this way leaks it.
+    Return(NonNull<u8>),
+}
+
+struct Step {
+    expected: Option<(Op, Option<NonZeroLayout>)>,
+    action: Action,
+}
+
+type Handler<'a> = Box<dyn Fn(&Call) -> Action + 'a>;
+
+/// A scriptable allocator for unit-testing code that interacts with divvy allocators.
+///
+/// Each call that can fail consumes the next scripted step, which may assert the kind of
+/// operation and layout it expects before performing its [Action]. Once the script is
+/// exhausted, calls are passed to the handler if there is one, and forwarded to the
+/// backing allocator otherwise. Deallocations never consume steps. Every call is recorded
+/// and can be inspected with [calls](Self::calls).
+///
+/// ```ignore
+/// let mock = MockAlloc::new(Global);
+/// mock.expect(Op::Allocate, layout, Action::Forward);
+/// mock.expect(Op::Grow, bigger, Action::Fail);
+///
+/// let result = vec_push_twice(&mock);
+///
+/// assert!(result.is_err());
+/// mock.assert_done();
+/// ```
+pub struct MockAlloc<'a, A> {
+    backing: A,
+    script: RefCell<VecDeque<Step>>,
+    handler: Option<Handler<'a>>,
+    calls: RefCell<Vec<Call>>,
+    injected: RefCell<Vec<NonNull<u8>>>,
+}
+
+impl<'a, A> MockAlloc<'a, A> {
+    /// Create a mock that forwards every call to `backing` until steps are scripted.
+    pub fn new(backing: A) -> Self {
+        Self {
+            backing,
+            script: RefCell::new(VecDeque::new()),
+            handler: None,
+            calls: RefCell::new(Vec::new()),
+            injected: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Create a mock that decides how to respond to each unscripted call with `handler`.
+    pub fn with_handler<F>(backing: A, handler: F) -> Self
+    where
+        F: Fn(&Call) -> Action + 'a,
+    {
+        Self {
+            handler: Some(Box::new(handler)),
+            ..Self::new(backing)
+        }
+    }
+
+    /// Script the response to the next call, whatever it is.
+    pub fn push(&self, action: Action) -> &Self {
+        self.script.borrow_mut().push_back(Step {
+            expected: None,
+            action,
+        });
+        self
+    }
+
+    /// Script the response to the next call, asserting that it is `op` with the given
+    /// layout.
+    pub fn expect(&self, op: Op, layout: NonZeroLayout, action: Action) -> &Self {
+        self.script.borrow_mut().push_back(Step {
+            expected: Some((op, Some(layout))),
+            action,
+        });
+        self
+    }
+
+    /// Script the response to the next call, asserting only that it is `op`.
+    pub fn expect_op(&self, op: Op, action: Action) -> &Self {
+        self.script.borrow_mut().push_back(Step {
+            expected: Some((op, None)),
+            action,
+        });
+        self
+    }
+
+    /// Script `n` consecutive failures.
+    pub fn fail_next(&self, n: usize) -> &Self {
+        for _ in 0..n {
+            self.push(Action::Fail);
+        }
+        self
+    }
+
+    /// The number of scripted steps not yet consumed.
+    pub fn remaining(&self) -> usize {
+        self.script.borrow().len()
+    }
+
+    /// Panic if any scripted steps have not been consumed.
+    #[track_caller]
+    pub fn assert_done(&self) {
+        let remaining = self.remaining();
+        assert!(
+            remaining == 0,
+            "{remaining} scripted allocator steps were never reached"
+        );
+    }
+
+    /// Every call made so far, in order.
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.borrow().clone()
+    }
+
+    /// The number of calls of the given kind made so far.
+    pub fn count(&self, op: Op) -> usize {
+        self.calls.borrow().iter().filter(|c| c.op == op).count()
+    }
+
+    /// Forget all recorded calls.
+    pub fn clear_calls(&self) {
+        self.calls.borrow_mut().clear();
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.backing
+    }
+
+    #[track_caller]
+    fn next_action(&self, call: Call) -> Action {
+        self.calls.borrow_mut().push(call);
+
+        let step = self.script.borrow_mut().pop_front();
+        let Some(step) = step else {
+            return match &self.handler {
+                Some(handler) => handler(&call),
+                None => Action::Forward,
+            };
+        };
+
+        if let Some((op, layout)) = step.expected {
+            assert!(
+                call.op == op,
+                "expected allocator call {op:?}, got {:?}",
+                call.op
+            );
+            if let Some(layout) = layout {
+                assert!(
+                    call.layout == layout,
+                    "expected {op:?} with {:?}, got {:?}",
+                    layout.get(),
+                    call.layout.get()
+                );
+            }
+        }
+        step.action
+    }
+
+    fn is_injected(&self, ptr: NonNull<u8>) -> bool {
+        self.injected.borrow().contains(&ptr)
+    }
+
+    fn forget_injected(&self, ptr: NonNull<u8>) {
+        self.injected.borrow_mut().retain(|&p| p != ptr);
+    }
+
+    #[track_caller]
+    fn respond<F>(&self, call: Call, forward: F) -> Result<NonNull<u8>, AllocError>
+    where
+        F: FnOnce() -> Result<NonNull<u8>, AllocError>,
+    {
+        match self.next_action(call) {
+            Action::Forward => {
+                if let Some(ptr) = call.ptr {
+                    assert!(
+                        !self.is_injected(ptr),
+                        "cannot forward {:?} of injected block {ptr:p}",
+                        call.op
+                    );
+                }
+                forward()
+            }
+            Action::Fail => Err(AllocError),
+            Action::Return(new) => {
+                if let Some(ptr) = call.ptr {
+                    self.forget_injected(ptr);
+                }
+                self.injected.borrow_mut().push(new);
+                Ok(new)
+            }
+        }
+    }
+
+    #[track_caller]
+    fn respond_in_place<F>(&self, call: Call, forward: F) -> Result<(), AllocError>
+    where
+        F: FnOnce() -> Result<(), AllocError>,
+    {
+        match self.next_action(call) {
+            Action::Forward => {
+                if let Some(ptr) = call.ptr {
+                    if self.is_injected(ptr) {
+                        return Err(AllocError);
+                    }
+                }
+                forward()
+            }
+            Action::Fail => Err(AllocError),
+            Action::Return(_) => panic!("{:?} cannot return a new address", call.op),
+        }
+    }
+}
+
+fn call(op: Op, layout: NonZeroLayout) -> Call {
+    Call {
+        op,
+        layout,
+        old_layout: None,
+        ptr: None,
+    }
+}
+
+fn resize(op: Op, ptr: NonNull<u8>, old_layout: NonZeroLayout, layout: NonZeroLayout) -> Call {
+    Call {
+        op,
+        layout,
+        old_layout: Some(old_layout),
+        ptr: Some(ptr),
+    }
+}
+
+impl<A> Deallocator for MockAlloc<'_, A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        self.calls
+            .borrow_mut()
+            .push(resize(Op::Deallocate, ptr, layout, layout));
+        if self.is_injected(ptr) {
+            self.forget_injected(ptr);
+        } else {
+            unsafe { self.backing.deallocate(ptr, layout) }
+        }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        let call = resize(Op::TryShrink, ptr, old_layout, new_layout);
+        self.respond_in_place(call, || unsafe {
+            self.backing.try_shrink(ptr, old_layout, new_layout)
+        })
+    }
+}
+
+unsafe impl<A> Allocator for MockAlloc<'_, A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.respond(call(Op::Allocate, layout), || self.backing.allocate(layout))
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        let mut size = layout.size();
+        let ptr = self.respond(call(Op::AllocateAtLeast, layout), || {
+            let (ptr, actual) = self.backing.allocate_at_least(layout)?;
+            size = actual;
+            Ok(ptr)
+        })?;
+        Ok((ptr, size))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.respond(call(Op::AllocateZeroed, layout), || {
+            self.backing.allocate_zeroed(layout)
+        })
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.respond(call(Op::AllocateFilled, layout), || {
+            self.backing.allocate_filled(layout, byte)
+        })
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let call = resize(Op::Grow, ptr, old_layout, new_layout);
+        self.respond(call, || unsafe {
+            self.backing.grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let call = resize(Op::GrowZeroed, ptr, old_layout, new_layout);
+        self.respond(call, || unsafe {
+            self.backing.grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let call = resize(Op::Shrink, ptr, old_layout, new_layout);
+        self.respond(call, || unsafe {
+            self.backing.shrink(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        let call = resize(Op::TryGrow, ptr, old_layout, new_layout);
+        self.respond_in_place(call, || unsafe {
+            self.backing.try_grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        let call = resize(Op::TryGrowZeroed, ptr, old_layout, new_layout);
+        self.respond_in_place(call, || unsafe {
+            self.backing.try_grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+}
+
+impl<A> Debug for MockAlloc<'_, A>
+where
+    A: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockAlloc")
+            .field("backing", &self.backing)
+            .field("remaining", &self.remaining())
+            .field("calls", &self.calls.borrow().len())
+            .finish_non_exhaustive()
+    }
+}
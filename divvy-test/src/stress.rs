@@ -0,0 +1,363 @@
+use std::{ptr::NonNull, vec::Vec};
+
+use divvy_core::{Allocator, NonZeroLayout};
+
+use crate::{assert_aligned, assert_filled, Config, Rng};
+
+/// How a [Stress] run chooses block sizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SizeDist {
+    /// Uniformly between `min` and `max`, inclusive. Bounds must satisfy `min <= max`
+    /// and `max > 0`, here and in `LogUniform`.
+    Uniform { min: usize, max: usize },
+    /// Uniformly over powers of two between `min` and `max`, then uniformly within that
+    /// power of two. This favours small blocks, much like most real workloads.
+    LogUniform { min: usize, max: usize },
+    /// Uniformly from a fixed set of sizes.
+    Choice(Vec<usize>),
+}
+
+impl SizeDist {
+    fn sample(&self, rng: &mut Rng) -> usize {
+        let size = match self {
+            Self::Uniform { min, max } => min + rng.below(max - min + 1),
+            Self::LogUniform { min, max } => {
+                let low = min.max(&1).ilog2();
+                let high = max.ilog2();
+                let shift = low + rng.below((high - low + 1) as usize) as u32;
+                let base = 1usize << shift;
+                (base + rng.below(base)).clamp(*min, *max)
+            }
+            Self::Choice(sizes) => sizes[rng.below(sizes.len())],
+        };
+        size.max(1)
+    }
+}
+
+/// How a [Stress] run chooses block alignments. Every alignment must be a power of two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlignDist {
+    /// Always the same alignment.
+    Fixed(usize),
+    /// Uniformly over powers of two up to and including `max`.
+    PowersOfTwo { max: usize },
+    /// Uniformly from a fixed set of alignments.
+    Choice(Vec<usize>),
+}
+
+impl AlignDist {
+    fn sample(&self, rng: &mut Rng) -> usize {
+        match self {
+            Self::Fixed(align) => *align,
+            Self::PowersOfTwo { max } => 1 << rng.below(max.trailing_zeros() as usize + 1),
+            Self::Choice(aligns) => aligns[rng.below(aligns.len())],
+        }
+    }
+}
+
+/// The relative frequency of each operation in a [Stress] run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weights {
+    pub allocate: u32,
+    pub deallocate: u32,
+    pub grow: u32,
+    pub shrink: u32,
+    pub try_grow: u32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            allocate: 2,
+            deallocate: 1,
+            grow: 1,
+            shrink: 1,
+            try_grow: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Choice {
+    Allocate,
+    Deallocate,
+    Grow,
+    Shrink,
+    TryGrow,
+}
+
+impl Weights {
+    fn choose(&self, rng: &mut Rng) -> Choice {
+        let choices = [
+            (self.allocate, Choice::Allocate),
+            (self.deallocate, Choice::Deallocate),
+            (self.grow, Choice::Grow),
+            (self.shrink, Choice::Shrink),
+            (self.try_grow, Choice::TryGrow),
+        ];
+        let total: u32 = choices.iter().map(|&(w, _)| w).sum();
+        assert!(total > 0, "at least one operation weight must be non-zero");
+
+        let mut pick = rng.below(total as usize) as u32;
+        for (weight, choice) in choices {
+            if pick < weight {
+                return choice;
+            }
+            pick -= weight;
+        }
+        unreachable!()
+    }
+}
+
+/// Counters collected over a [Stress] run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StressReport {
+    pub operations: usize,
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub grows: usize,
+    pub shrinks: usize,
+    pub in_place_grows: usize,
+    /// Requests the allocator refused. These are not errors.
+    pub failures: usize,
+    pub peak_live: usize,
+    pub peak_bytes: usize,
+}
+
+/// A randomized stress tester.
+///
+/// A run performs a seeded, reproducible interleaving of allocations, deallocations,
+/// grows, and shrinks. Every live block is filled with a pattern unique to it, and the
+/// pattern is verified before each block is resized or freed. New blocks are checked for
+/// alignment and for overlap with every other live block. Any violation panics.
+///
+/// ```ignore
+/// let report = Stress {
+///     iterations: 100_000,
+///     sizes: SizeDist::LogUniform { min: 8, max: 1 << 20 },
+///     ..Stress::default()
+/// }
+/// .run(&my_allocator);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stress {
+    pub iterations: usize,
+    /// The maximum number of blocks kept alive at once.
+    pub max_live: usize,
+    pub sizes: SizeDist,
+    pub aligns: AlignDist,
+    pub weights: Weights,
+    pub seed: u64,
+}
+
+impl Default for Stress {
+    fn default() -> Self {
+        Self::from(&Config::default())
+    }
+}
+
+impl From<&Config> for Stress {
+    fn from(config: &Config) -> Self {
+        Self {
+            iterations: config.iterations,
+            max_live: config.max_live,
+            sizes: SizeDist::Uniform {
+                min: 1,
+                max: config.max_size,
+            },
+            aligns: AlignDist::PowersOfTwo {
+                max: config.max_align,
+            },
+            weights: Weights::default(),
+            seed: config.seed,
+        }
+    }
+}
+
+struct Live {
+    ptr: NonNull<u8>,
+    layout: NonZeroLayout,
+    tag: u8,
+}
+
+impl Stress {
+    /// Run the stress test against `allocator`, panicking on the first violation.
+    #[track_caller]
+    pub fn run<A: Allocator>(&self, allocator: &A) -> StressReport {
+        let mut rng = Rng::new(self.seed);
+        let mut live: Vec<Live> = Vec::new();
+        let mut report = StressReport::default();
+        let mut bytes = 0;
+
+        for step in 0..self.iterations {
+            let tag = step as u8;
+            report.operations += 1;
+
+            match self.weights.choose(&mut rng) {
+                Choice::Allocate if live.len() < self.max_live => {
+                    let Some(layout) = self.layout(&mut rng) else {
+                        continue;
+                    };
+                    let (result, contents) = match rng.below(3) {
+                        0 => (allocator.allocate(layout), None),
+                        1 => (allocator.allocate_zeroed(layout), Some(0)),
+                        _ => (allocator.allocate_filled(layout, tag), Some(tag)),
+                    };
+                    let Ok(ptr) = result else {
+                        report.failures += 1;
+                        continue;
+                    };
+                    unsafe {
+                        if let Some(byte) = contents {
+                            assert_filled(ptr, 0, layout.size(), byte, "new block");
+                        }
+                    }
+                    let block = Live { ptr, layout, tag };
+                    check_new(&live, &block);
+                    report.allocations += 1;
+                    bytes += layout.size();
+                    live.push(block);
+                }
+                Choice::Deallocate if !live.is_empty() => {
+                    let block = live.swap_remove(rng.below(live.len()));
+                    unsafe {
+                        verify(&block, "deallocate");
+                        allocator.deallocate(block.ptr, block.layout);
+                    }
+                    report.deallocations += 1;
+                    bytes -= block.layout.size();
+                }
+                choice @ (Choice::Grow | Choice::Shrink) if !live.is_empty() => {
+                    let block = live.swap_remove(rng.below(live.len()));
+                    let grow = matches!(choice, Choice::Grow);
+                    let size = if grow {
+                        block.layout.size().checked_add(self.sizes.sample(&mut rng))
+                    } else {
+                        Some(1 + rng.below(block.layout.size()))
+                    };
+                    let Some(layout) =
+                        size.and_then(|s| NonZeroLayout::from_size_align(s, block.layout.align()))
+                    else {
+                        live.push(block);
+                        continue;
+                    };
+
+                    let result = unsafe {
+                        verify(&block, "resize");
+                        if grow {
+                            allocator.grow(block.ptr, block.layout, layout)
+                        } else {
+                            allocator.shrink(block.ptr, block.layout, layout)
+                        }
+                    };
+                    let Ok(ptr) = result else {
+                        unsafe { verify(&block, "failed resize") };
+                        report.failures += 1;
+                        live.push(block);
+                        continue;
+                    };
+
+                    let kept = block.layout.size().min(layout.size());
+                    unsafe { assert_pattern(ptr, kept, block.tag, "resize") };
+                    let resized = Live { ptr, layout, tag };
+                    check_new(&live, &resized);
+                    if grow {
+                        report.grows += 1;
+                    } else {
+                        report.shrinks += 1;
+                    }
+                    bytes = bytes - block.layout.size() + layout.size();
+                    live.push(resized);
+                }
+                Choice::TryGrow if !live.is_empty() => {
+                    let mut block = live.swap_remove(rng.below(live.len()));
+                    let size = block.layout.size().checked_add(self.sizes.sample(&mut rng));
+                    let layout =
+                        size.and_then(|s| NonZeroLayout::from_size_align(s, block.layout.align()));
+                    if let Some(layout) = layout {
+                        if unsafe { allocator.try_grow(block.ptr, block.layout, layout) }.is_ok() {
+                            let old = block.layout.size();
+                            unsafe { assert_pattern(block.ptr, old, block.tag, "try_grow") };
+                            bytes = bytes - old + layout.size();
+                            block.layout = layout;
+                            block.tag = tag;
+                            check_new(&live, &block);
+                            report.in_place_grows += 1;
+                        } else {
+                            unsafe { verify(&block, "failed try_grow") };
+                            report.failures += 1;
+                        }
+                    }
+                    live.push(block);
+                }
+                _ => {}
+            }
+
+            report.peak_live = report.peak_live.max(live.len());
+            report.peak_bytes = report.peak_bytes.max(bytes);
+        }
+
+        for block in live {
+            unsafe {
+                verify(&block, "deallocate");
+                allocator.deallocate(block.ptr, block.layout);
+            }
+            report.deallocations += 1;
+        }
+
+        report
+    }
+
+    fn layout(&self, rng: &mut Rng) -> Option<NonZeroLayout> {
+        NonZeroLayout::from_size_align(self.sizes.sample(rng), self.aligns.sample(rng))
+    }
+}
+
+/// Check that a block that just became live is aligned and disjoint from every other
+/// live block, then stamp it with its pattern.
+#[track_caller]
+fn check_new(live: &[Live], block: &Live) {
+    assert_aligned(block.ptr, block.layout);
+
+    let start = block.ptr.as_ptr().addr();
+    let end = start + block.layout.size();
+    for other in live {
+        let other_start = other.ptr.as_ptr().addr();
+        let other_end = other_start + other.layout.size();
+        assert!(
+            end <= other_start || other_end <= start,
+            "block {:p} of {} bytes overlaps live block {:p} of {} bytes",
+            block.ptr,
+            block.layout.size(),
+            other.ptr,
+            other.layout.size()
+        );
+    }
+
+    let bytes = unsafe { std::slice::from_raw_parts_mut(block.ptr.as_ptr(), block.layout.size()) };
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = pattern(block.tag, i);
+    }
+}
+
+#[inline]
+fn pattern(tag: u8, i: usize) -> u8 {
+    tag ^ (i as u8).wrapping_mul(31)
+}
+
+#[track_caller]
+unsafe fn verify(block: &Live, what: &str) {
+    unsafe { assert_pattern(block.ptr, block.layout.size(), block.tag, what) };
+}
+
+#[track_caller]
+unsafe fn assert_pattern(ptr: NonNull<u8>, size: usize, tag: u8, what: &str) {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), size) };
+    for (i, &byte) in bytes.iter().enumerate() {
+        let expected = pattern(tag, i);
+        assert!(
+            byte == expected,
+            "{what}: byte {i} of block {ptr:p} is {byte:#04x}, expected {expected:#04x}"
+        );
+    }
+}
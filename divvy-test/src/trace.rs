@@ -0,0 +1,474 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    io::{self, Read, Write},
+    ptr::NonNull,
+    sync::Mutex,
+    vec::Vec,
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+const MAGIC: &[u8; 4] = b"DVTR";
+const VERSION: u8 = 1;
+
+/// The kind of operation recorded in a [Trace].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TraceOp {
+    Allocate = 0,
+    AllocateZeroed = 1,
+    Deallocate = 2,
+    Grow = 3,
+    GrowZeroed = 4,
+    Shrink = 5,
+}
+
+impl TraceOp {
+    fn from_u8(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => Self::Allocate,
+            1 => Self::AllocateZeroed,
+            2 => Self::Deallocate,
+            3 => Self::Grow,
+            4 => Self::GrowZeroed,
+            5 => Self::Shrink,
+            _ => return None,
+        })
+    }
+}
+
+/// A single recorded operation. `id` identifies the block across its lifetime, and
+/// `size` and `align` describe its layout after the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub op: TraceOp,
+    pub id: u64,
+    pub size: usize,
+    pub align: usize,
+}
+
+/// An error produced when decoding a serialized [Trace].
+#[derive(Debug)]
+pub enum TraceError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    Corrupt,
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "i/o error: {err}"),
+            Self::BadMagic => f.write_str("not an allocation trace"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported trace version {v}"),
+            Self::Corrupt => f.write_str("corrupt allocation trace"),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl From<io::Error> for TraceError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Corrupt
+        } else {
+            Self::Io(err)
+        }
+    }
+}
+
+/// A recorded sequence of allocator operations.
+///
+/// The serialized form is a short header followed by one record per event: the op byte,
+/// the block id and size as LEB128 varints, and the base-two log of the alignment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_varint(&mut writer, self.events.len() as u64)?;
+        for event in &self.events {
+            writer.write_all(&[event.op as u8])?;
+            write_varint(&mut writer, event.id)?;
+            write_varint(&mut writer, event.size as u64)?;
+            writer.write_all(&[event.align.trailing_zeros() as u8])?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, TraceError> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(TraceError::BadMagic);
+        }
+        if header[4] != VERSION {
+            return Err(TraceError::UnsupportedVersion(header[4]));
+        }
+
+        let len = read_varint(&mut reader)?;
+        let mut events = Vec::new();
+        for _ in 0..len {
+            let op = TraceOp::from_u8(read_byte(&mut reader)?).ok_or(TraceError::Corrupt)?;
+            let id = read_varint(&mut reader)?;
+            let size =
+                usize::try_from(read_varint(&mut reader)?).map_err(|_| TraceError::Corrupt)?;
+            let shift = read_byte(&mut reader)?;
+            let align = 1usize
+                .checked_shl(shift.into())
+                .ok_or(TraceError::Corrupt)?;
+            events.push(Event {
+                op,
+                id,
+                size,
+                align,
+            });
+        }
+        Ok(Self { events })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TraceError> {
+        Self::read_from(bytes)
+    }
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, TraceError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(reader)?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(TraceError::Corrupt)
+}
+
+fn read_byte<R: Read>(reader: &mut R) -> Result<u8, TraceError> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    ids: HashMap<usize, u64>,
+    next_id: u64,
+    trace: Trace,
+}
+
+/// An allocator wrapper that records every successful operation performed through it
+/// into a [Trace], which can later be [replayed](replay) against other allocators.
+///
+/// The recorder allocates its own bookkeeping from the global allocator, so it cannot be
+/// used to back the global allocator itself.
+#[derive(Debug, Default)]
+pub struct Recorder<A> {
+    allocator: A,
+    state: Mutex<RecorderState>,
+}
+
+impl<A> Recorder<A> {
+    pub fn new(allocator: A) -> Self {
+        Self {
+            allocator,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    /// Take the trace recorded so far, leaving an empty one in its place. Blocks that
+    /// are still live keep their ids.
+    pub fn take_trace(&self) -> Trace {
+        std::mem::take(&mut self.state().trace)
+    }
+
+    pub fn into_inner(self) -> (A, Trace) {
+        let state = self.state.into_inner().unwrap_or_else(|e| e.into_inner());
+        (self.allocator, state.trace)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RecorderState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record_new(&self, op: TraceOp, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        let mut state = self.state();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.ids.insert(ptr.as_ptr().addr(), id);
+        state.trace.events.push(event(op, id, layout));
+    }
+
+    fn record_resize(
+        &self,
+        op: TraceOp,
+        old: NonNull<u8>,
+        new: NonNull<u8>,
+        layout: NonZeroLayout,
+    ) {
+        let mut state = self.state();
+        let Some(id) = state.ids.remove(&old.as_ptr().addr()) else {
+            return;
+        };
+        state.ids.insert(new.as_ptr().addr(), id);
+        state.trace.events.push(event(op, id, layout));
+    }
+
+    fn record_free(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        let mut state = self.state();
+        let Some(id) = state.ids.remove(&ptr.as_ptr().addr()) else {
+            return;
+        };
+        state
+            .trace
+            .events
+            .push(event(TraceOp::Deallocate, id, layout));
+    }
+}
+
+fn event(op: TraceOp, id: u64, layout: NonZeroLayout) -> Event {
+    Event {
+        op,
+        id,
+        size: layout.size(),
+        align: layout.align(),
+    }
+}
+
+impl<A> Deallocator for Recorder<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        self.record_free(ptr, layout);
+        unsafe { self.allocator.deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_shrink(ptr, old_layout, new_layout)? };
+        self.record_resize(TraceOp::Shrink, ptr, ptr, new_layout);
+        Ok(())
+    }
+}
+
+unsafe impl<A> Allocator for Recorder<A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.allocator.allocate(layout)?;
+        self.record_new(TraceOp::Allocate, ptr, layout);
+        Ok(ptr)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        let (ptr, size) = self.allocator.allocate_at_least(layout)?;
+        self.record_new(TraceOp::Allocate, ptr, layout);
+        Ok((ptr, size))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.allocator.allocate_zeroed(layout)?;
+        self.record_new(TraceOp::AllocateZeroed, ptr, layout);
+        Ok(ptr)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.allocator.allocate_filled(layout, byte)?;
+        self.record_new(TraceOp::Allocate, ptr, layout);
+        Ok(ptr)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new = unsafe { self.allocator.grow(ptr, old_layout, new_layout)? };
+        self.record_resize(TraceOp::Grow, ptr, new, new_layout);
+        Ok(new)
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new = unsafe { self.allocator.grow_zeroed(ptr, old_layout, new_layout)? };
+        self.record_resize(TraceOp::GrowZeroed, ptr, new, new_layout);
+        Ok(new)
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new = unsafe { self.allocator.shrink(ptr, old_layout, new_layout)? };
+        self.record_resize(TraceOp::Shrink, ptr, new, new_layout);
+        Ok(new)
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow(ptr, old_layout, new_layout)? };
+        self.record_resize(TraceOp::Grow, ptr, ptr, new_layout);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            self.allocator
+                .try_grow_zeroed(ptr, old_layout, new_layout)?
+        };
+        self.record_resize(TraceOp::GrowZeroed, ptr, ptr, new_layout);
+        Ok(())
+    }
+}
+
+/// Counters collected while [replaying](replay) a trace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub operations: usize,
+    /// Operations the allocator refused. Blocks whose allocation failed are skipped for
+    /// the rest of the trace.
+    pub failures: usize,
+    /// Events that referred to unknown blocks, or that had an invalid layout.
+    pub skipped: usize,
+    /// Blocks still live at the end of the trace, which are freed before returning.
+    pub leaked: usize,
+}
+
+/// Drive `allocator` with a recorded trace, for example to benchmark it against a real
+/// workload.
+pub fn replay<A: Allocator>(trace: &Trace, allocator: &A) -> ReplayReport {
+    let mut live: HashMap<u64, (NonNull<u8>, NonZeroLayout)> = HashMap::new();
+    let mut report = ReplayReport::default();
+
+    for event in &trace.events {
+        report.operations += 1;
+        let Some(layout) = NonZeroLayout::from_size_align(event.size, event.align) else {
+            report.skipped += 1;
+            continue;
+        };
+
+        let result = match event.op {
+            TraceOp::Allocate | TraceOp::AllocateZeroed => {
+                if live.contains_key(&event.id) {
+                    report.skipped += 1;
+                    continue;
+                }
+                if event.op == TraceOp::Allocate {
+                    allocator.allocate(layout)
+                } else {
+                    allocator.allocate_zeroed(layout)
+                }
+            }
+            TraceOp::Deallocate => {
+                let Some((ptr, old_layout)) = live.remove(&event.id) else {
+                    report.skipped += 1;
+                    continue;
+                };
+                unsafe { allocator.deallocate(ptr, old_layout) };
+                continue;
+            }
+            TraceOp::Grow | TraceOp::GrowZeroed | TraceOp::Shrink => {
+                let Some(&(ptr, old_layout)) = live.get(&event.id) else {
+                    report.skipped += 1;
+                    continue;
+                };
+                unsafe {
+                    match event.op {
+                        TraceOp::Grow if layout.size() >= old_layout.size() => {
+                            allocator.grow(ptr, old_layout, layout)
+                        }
+                        TraceOp::GrowZeroed if layout.size() >= old_layout.size() => {
+                            allocator.grow_zeroed(ptr, old_layout, layout)
+                        }
+                        TraceOp::Shrink if layout.size() <= old_layout.size() => {
+                            allocator.shrink(ptr, old_layout, layout)
+                        }
+                        _ => {
+                            report.skipped += 1;
+                            continue;
+                        }
+                    }
+                }
+            }
+        };
+
+        match result {
+            Ok(ptr) => {
+                live.insert(event.id, (ptr, layout));
+            }
+            Err(_) => report.failures += 1,
+        }
+    }
+
+    report.leaked = live.len();
+    for (ptr, layout) in live.into_values() {
+        unsafe { allocator.deallocate(ptr, layout) };
+    }
+    report
+}
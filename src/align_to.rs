@@ -0,0 +1,200 @@
+use core::{cmp, ptr::NonNull};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+/// An allocator wrapper that raises the alignment of every request to at least `ALIGN`.
+///
+/// Layouts are adjusted identically on every call, so blocks are deallocated, grown, and
+/// shrunk with the same layout the wrapped allocator originally saw. `ALIGN` must be a
+/// power of two.
+#[derive(Debug, Default, Clone)]
+pub struct AlignTo<A, const ALIGN: usize> {
+    allocator: A,
+}
+
+impl<A, const ALIGN: usize> AlignTo<A, ALIGN> {
+    const VALID: () = assert!(ALIGN.is_power_of_two(), "alignment must be a power of two");
+
+    pub const fn new(allocator: A) -> Self {
+        let () = Self::VALID;
+        Self { allocator }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+
+    #[inline]
+    fn align(layout: NonZeroLayout) -> Result<NonZeroLayout, AllocError> {
+        if layout.align() >= ALIGN {
+            return Ok(layout);
+        }
+        NonZeroLayout::from_size_align(layout.size(), cmp::max(layout.align(), ALIGN))
+            .ok_or(AllocError)
+    }
+
+    /// Adjust the layout of a block that was previously allocated through this wrapper,
+    /// which is therefore known to be valid.
+    #[inline]
+    unsafe fn align_existing(layout: NonZeroLayout) -> NonZeroLayout {
+        match Self::align(layout) {
+            Ok(layout) => layout,
+            Err(_) => unsafe { core::hint::unreachable_unchecked() },
+        }
+    }
+}
+
+impl<A, const ALIGN: usize> Deallocator for AlignTo<A, ALIGN>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe {
+            let layout = Self::align_existing(layout);
+            self.allocator.deallocate(ptr, layout)
+        }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            let old_layout = Self::align_existing(old_layout);
+            let new_layout = Self::align(new_layout)?;
+            self.allocator.try_shrink(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+unsafe impl<A, const ALIGN: usize> Allocator for AlignTo<A, ALIGN>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate(Self::align(layout)?)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.allocator.allocate_at_least(Self::align(layout)?)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_zeroed(Self::align(layout)?)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_filled(Self::align(layout)?, byte)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            let old_layout = Self::align_existing(old_layout);
+            let new_layout = Self::align(new_layout)?;
+            self.allocator.grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            let old_layout = Self::align_existing(old_layout);
+            let new_layout = Self::align(new_layout)?;
+            self.allocator.grow_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            let old_layout = Self::align_existing(old_layout);
+            let new_layout = Self::align(new_layout)?;
+            self.allocator.shrink(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            let old_layout = Self::align_existing(old_layout);
+            let new_layout = Self::align(new_layout)?;
+            self.allocator.try_grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            let old_layout = Self::align_existing(old_layout);
+            let new_layout = Self::align(new_layout)?;
+            self.allocator.try_grow_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+impl<A, const ALIGN: usize> Owns for AlignTo<A, ALIGN>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        match Self::align(layout) {
+            Ok(layout) => self.allocator.owns(ptr, layout),
+            Err(_) => false,
+        }
+    }
+}
+
+impl<A, const ALIGN: usize> Trim for AlignTo<A, ALIGN>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
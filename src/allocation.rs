@@ -0,0 +1,195 @@
+use core::{
+    cmp,
+    mem::ManuallyDrop,
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+/// An owned block of raw memory, deallocated when dropped.
+///
+/// The memory is uninitialized unless it was allocated with
+/// [try_new_zeroed_in](Allocation::try_new_zeroed_in), so reading from it before writing
+/// is undefined behavior.
+#[derive(Debug)]
+pub struct Allocation<A>
+where
+    A: Deallocator,
+{
+    ptr: NonNull<u8>,
+    layout: NonZeroLayout,
+    allocator: A,
+}
+
+unsafe impl<A> Send for Allocation<A> where A: Deallocator + Send {}
+unsafe impl<A> Sync for Allocation<A> where A: Deallocator + Sync {}
+
+impl<A> Allocation<A>
+where
+    A: Allocator,
+{
+    pub fn try_new_in(layout: NonZeroLayout, allocator: A) -> Result<Self, AllocError> {
+        let ptr = allocator.allocate(layout)?;
+        Ok(Self {
+            ptr,
+            layout,
+            allocator,
+        })
+    }
+
+    pub fn try_new_zeroed_in(layout: NonZeroLayout, allocator: A) -> Result<Self, AllocError> {
+        let ptr = allocator.allocate_zeroed(layout)?;
+        Ok(Self {
+            ptr,
+            layout,
+            allocator,
+        })
+    }
+
+    pub fn new_in(layout: NonZeroLayout, allocator: A) -> Self {
+        Self::try_new_in(layout, allocator).expect("allocation failed")
+    }
+
+    pub fn new_zeroed_in(layout: NonZeroLayout, allocator: A) -> Self {
+        Self::try_new_zeroed_in(layout, allocator).expect("allocation failed")
+    }
+
+    /// Grow the block to `new_layout`, preserving its contents. The block may move, and
+    /// always does if the alignment increases. On failure, the block is left unchanged.
+    ///
+    /// # Panics
+    /// Panics if `new_layout` is smaller than the current layout.
+    pub fn grow(&mut self, new_layout: NonZeroLayout) -> Result<(), AllocError> {
+        assert!(
+            new_layout.size() >= self.layout.size(),
+            "cannot grow to a smaller size"
+        );
+        if new_layout.align() > self.layout.align() {
+            return self.relocate(new_layout, false);
+        }
+        self.ptr = unsafe { self.allocator.grow(self.ptr, self.layout, new_layout)? };
+        self.layout = new_layout;
+        Ok(())
+    }
+
+    /// Grow the block to `new_layout`, preserving its contents and zeroing the new tail.
+    /// The block may move, and always does if the alignment increases. On failure, the
+    /// block is left unchanged.
+    ///
+    /// # Panics
+    /// Panics if `new_layout` is smaller than the current layout.
+    pub fn grow_zeroed(&mut self, new_layout: NonZeroLayout) -> Result<(), AllocError> {
+        assert!(
+            new_layout.size() >= self.layout.size(),
+            "cannot grow to a smaller size"
+        );
+        if new_layout.align() > self.layout.align() {
+            return self.relocate(new_layout, true);
+        }
+        self.ptr = unsafe {
+            self.allocator
+                .grow_zeroed(self.ptr, self.layout, new_layout)?
+        };
+        self.layout = new_layout;
+        Ok(())
+    }
+
+    /// Shrink the block to `new_layout`, preserving the contents that still fit. The
+    /// block may move, and always does if the alignment increases. On failure, the
+    /// block is left unchanged.
+    ///
+    /// # Panics
+    /// Panics if `new_layout` is larger than the current layout.
+    pub fn shrink(&mut self, new_layout: NonZeroLayout) -> Result<(), AllocError> {
+        assert!(
+            new_layout.size() <= self.layout.size(),
+            "cannot shrink to a larger size"
+        );
+        if new_layout.align() > self.layout.align() {
+            return self.relocate(new_layout, false);
+        }
+        self.ptr = unsafe { self.allocator.shrink(self.ptr, self.layout, new_layout)? };
+        self.layout = new_layout;
+        Ok(())
+    }
+
+    /// Move the block into a new one with a larger alignment. The allocator's resizing
+    /// methods may keep the block in place, so this is done by hand.
+    #[cold]
+    fn relocate(&mut self, new_layout: NonZeroLayout, zeroed: bool) -> Result<(), AllocError> {
+        let ptr = if zeroed {
+            self.allocator.allocate_zeroed(new_layout)?
+        } else {
+            self.allocator.allocate(new_layout)?
+        };
+
+        unsafe {
+            let size = cmp::min(self.layout.size(), new_layout.size());
+            ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), size);
+            self.allocator.deallocate(self.ptr, self.layout);
+        }
+        self.ptr = ptr;
+        self.layout = new_layout;
+        Ok(())
+    }
+}
+
+impl<A> Allocation<A>
+where
+    A: Deallocator,
+{
+    /// Take ownership of a block of memory.
+    ///
+    /// # Safety
+    /// `ptr` must have been allocated by `allocator` with `layout`, and must not be
+    /// deallocated by anything else.
+    pub unsafe fn from_raw_parts(ptr: NonNull<u8>, layout: NonZeroLayout, allocator: A) -> Self {
+        Self {
+            ptr,
+            layout,
+            allocator,
+        }
+    }
+
+    /// Release ownership of the block without deallocating it.
+    pub fn into_raw_parts(self) -> (NonNull<u8>, NonZeroLayout, A) {
+        let this = ManuallyDrop::new(self);
+        let allocator = unsafe { ptr::read(&this.allocator) };
+        (this.ptr, this.layout, allocator)
+    }
+
+    pub fn as_ptr(&self) -> NonNull<u8> {
+        self.ptr
+    }
+
+    pub fn as_slice_ptr(&self) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(self.ptr, self.len())
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Always returns `false`, as allocations are never empty. Provided for consistency
+    /// with [len](Allocation::len).
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn layout(&self) -> NonZeroLayout {
+        self.layout
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.allocator
+    }
+}
+
+impl<A> Drop for Allocation<A>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        unsafe { self.allocator.deallocate(self.ptr, self.layout) };
+    }
+}
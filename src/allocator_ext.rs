@@ -0,0 +1,101 @@
+use alloc::alloc::handle_alloc_error;
+use core::ptr::NonNull;
+
+use divvy_core::{Allocator, NonZeroLayout};
+
+/// Infallible counterparts to the [`Allocator`] methods.
+///
+/// On failure, each method calls [`handle_alloc_error`] with the requested layout
+/// rather than returning an error, giving the same out-of-memory behavior as the
+/// standard collections.
+pub trait AllocatorExt: Allocator {
+    /// Allocate a new block of memory, aborting on failure.
+    ///
+    /// See [`Allocator::allocate`].
+    #[inline]
+    fn allocate_or_abort(&self, layout: NonZeroLayout) -> NonNull<u8> {
+        match self.allocate(layout) {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(layout.get()),
+        }
+    }
+
+    /// Allocate a new block of zeroed memory, aborting on failure.
+    ///
+    /// See [`Allocator::allocate_zeroed`].
+    #[inline]
+    fn allocate_zeroed_or_abort(&self, layout: NonZeroLayout) -> NonNull<u8> {
+        match self.allocate_zeroed(layout) {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(layout.get()),
+        }
+    }
+
+    /// Allocate a new block of memory filled with `byte`, aborting on failure.
+    ///
+    /// See [`Allocator::allocate_filled`].
+    #[inline]
+    fn allocate_filled_or_abort(&self, layout: NonZeroLayout, byte: u8) -> NonNull<u8> {
+        match self.allocate_filled(layout, byte) {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(layout.get()),
+        }
+    }
+
+    /// Grow a previously allocated block of memory, aborting on failure.
+    ///
+    /// # Safety
+    ///
+    /// See [`Allocator::grow`].
+    #[inline]
+    unsafe fn grow_or_abort(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> NonNull<u8> {
+        match unsafe { self.grow(ptr, old_layout, new_layout) } {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(new_layout.get()),
+        }
+    }
+
+    /// Grow a previously allocated block of memory, zeroing the newly allocated
+    /// region, and aborting on failure.
+    ///
+    /// # Safety
+    ///
+    /// See [`Allocator::grow_zeroed`].
+    #[inline]
+    unsafe fn grow_zeroed_or_abort(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> NonNull<u8> {
+        match unsafe { self.grow_zeroed(ptr, old_layout, new_layout) } {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(new_layout.get()),
+        }
+    }
+
+    /// Shrink a previously allocated block of memory, aborting on failure.
+    ///
+    /// # Safety
+    ///
+    /// See [`Allocator::shrink`].
+    #[inline]
+    unsafe fn shrink_or_abort(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> NonNull<u8> {
+        match unsafe { self.shrink(ptr, old_layout, new_layout) } {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(new_layout.get()),
+        }
+    }
+}
+
+impl<A> AllocatorExt for A where A: Allocator + ?Sized {}
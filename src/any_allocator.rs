@@ -0,0 +1,177 @@
+use alloc::boxed::Box;
+use core::{fmt::Debug, ptr::NonNull};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+/// An owned, type-erased allocator.
+///
+/// `Allocator` is object safe, so borrowed allocators can already be passed around
+/// as `&dyn Allocator`. `AnyAllocator` covers the owned case, allowing an allocator
+/// of any type to be stored in a struct or passed across an API boundary without
+/// introducing a generic parameter. The allocator itself is boxed using the global
+/// allocator.
+pub struct AnyAllocator<'a> {
+    allocator: Box<dyn Allocator + 'a>,
+}
+
+impl<'a> AnyAllocator<'a> {
+    pub fn new<A>(allocator: A) -> Self
+    where
+        A: Allocator + 'a,
+    {
+        Self::from_box(Box::new(allocator))
+    }
+
+    pub fn from_box(allocator: Box<dyn Allocator + 'a>) -> Self {
+        Self { allocator }
+    }
+
+    pub fn get_ref(&self) -> &(dyn Allocator + 'a) {
+        &*self.allocator
+    }
+
+    pub fn into_inner(self) -> Box<dyn Allocator + 'a> {
+        self.allocator
+    }
+}
+
+impl<'a> Debug for AnyAllocator<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AnyAllocator").finish_non_exhaustive()
+    }
+}
+
+impl<'a> Deallocator for AnyAllocator<'a> {
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.allocator.deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe {
+            self.allocator
+                .try_shrink_at_least(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+unsafe impl<'a> Allocator for AnyAllocator<'a> {
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.allocator.allocate_at_least(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_zeroed(layout)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_filled(layout, byte)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(NonNull<u8>, usize), AllocError> {
+        unsafe { self.allocator.grow_at_least(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.reallocate(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe {
+            self.allocator
+                .try_grow_at_least(ptr, old_layout, new_layout)
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+/// An allocator wrapper that permits at most one allocation.
+pub type Once<A> = AtMost<A, 1>;
+
+/// An allocator wrapper that permits at most `N` allocations over its lifetime.
+///
+/// Once the limit has been reached, further allocations fail with `AllocError`.
+/// Deallocating does not free up a slot, and growing or shrinking an existing block
+/// does not count as a new allocation. Allocations that fail in the wrapped allocator
+/// are not counted.
+#[derive(Debug, Default)]
+pub struct AtMost<A, const N: usize> {
+    allocator: A,
+    count: AtomicUsize,
+}
+
+impl<A, const N: usize> AtMost<A, N> {
+    pub const fn new(allocator: A) -> Self {
+        Self {
+            allocator,
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of allocations that have been made.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of allocations that may still be made.
+    pub fn remaining(&self) -> usize {
+        N - self.count()
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+
+    #[inline]
+    fn counted<T>(&self, f: impl FnOnce(&A) -> Result<T, AllocError>) -> Result<T, AllocError> {
+        self.count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                (count < N).then_some(count + 1)
+            })
+            .map_err(|_| AllocError)?;
+
+        let result = f(&self.allocator);
+        if result.is_err() {
+            self.count.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl<A, const N: usize> Deallocator for AtMost<A, N>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.allocator.deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+unsafe impl<A, const N: usize> Allocator for AtMost<A, N>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.counted(|a| a.allocate(layout))
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.counted(|a| a.allocate_at_least(layout))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.counted(|a| a.allocate_zeroed(layout))
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.counted(|a| a.allocate_filled(layout, byte))
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow_zeroed(ptr, old_layout, new_layout) }
+    }
+}
+
+impl<A, const N: usize> Owns for AtMost<A, N>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A, const N: usize> Trim for AtMost<A, N>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
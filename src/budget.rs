@@ -0,0 +1,333 @@
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::{Trim, Watermarks};
+
+/// A memory budget that can be shared between several [`Limited`] allocators, enforcing
+/// a combined cap on the bytes they hand out.
+///
+/// Budgets can be nested with [child](Budget::child). A child has its own limit, and every
+/// byte charged to it is also charged to its parent, except for bytes covered by the
+/// child's reservation. The reservation is charged to the parent up front when the child
+/// is created, guaranteeing that it remains available, and is returned when the child is
+/// dropped.
+#[derive(Debug)]
+pub struct Budget<'a> {
+    limit: usize,
+    used: AtomicUsize,
+    reserved: usize,
+    parent: Option<&'a Budget<'a>>,
+    watermarks: Option<Watermarks>,
+}
+
+impl<'a> Budget<'a> {
+    /// Create a root budget of `limit` bytes.
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+            reserved: 0,
+            parent: None,
+            watermarks: None,
+        }
+    }
+
+    /// Create a sub-budget of `limit` bytes, reserving `reserved` of them from this
+    /// budget immediately. Fails if the reservation cannot be satisfied.
+    pub fn child(&'a self, limit: usize, reserved: usize) -> Result<Budget<'a>, AllocError> {
+        let reserved = reserved.min(limit);
+        if !self.try_charge(reserved) {
+            return Err(AllocError);
+        }
+        Ok(Budget {
+            limit,
+            used: AtomicUsize::new(0),
+            reserved,
+            parent: Some(self),
+            watermarks: None,
+        })
+    }
+
+    /// Watch the bytes charged to this budget, calling back when they cross the given
+    /// watermarks.
+    pub const fn with_watermarks(mut self, watermarks: Watermarks) -> Self {
+        self.watermarks = Some(watermarks);
+        self
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of bytes currently charged to this budget, including those
+    /// charged through its children.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes reserved for this budget in its parent.
+    pub fn reserved(&self) -> usize {
+        self.reserved
+    }
+
+    /// Returns the number of bytes that may still be charged without exceeding this
+    /// budget's own limit. Charges can still fail if an ancestor is exhausted.
+    pub fn remaining(&self) -> usize {
+        self.limit.saturating_sub(self.used())
+    }
+
+    /// Attempt to charge `size` bytes to this budget and its ancestors, returning `false`
+    /// and leaving every budget unchanged if any limit would be exceeded.
+    pub fn try_charge(&self, size: usize) -> bool {
+        let mut old = self.used.load(Ordering::Relaxed);
+        loop {
+            let Some(new) = old.checked_add(size).filter(|&new| new <= self.limit) else {
+                return false;
+            };
+
+            // The parent is charged before the new count is published, so that a
+            // concurrent release never returns bytes the parent has not been given.
+            let delta = self.excess(new) - self.excess(old);
+            if let Some(parent) = self.parent {
+                if !parent.try_charge(delta) {
+                    return false;
+                }
+            }
+
+            match self
+                .used
+                .compare_exchange_weak(old, new, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(current) => {
+                    if let Some(parent) = self.parent {
+                        parent.release(delta);
+                    }
+                    old = current;
+                }
+            }
+        }
+
+        if let Some(watermarks) = &self.watermarks {
+            watermarks.update(old + size);
+        }
+        true
+    }
+
+    /// Return `size` bytes previously charged with [try_charge](Budget::try_charge).
+    pub fn release(&self, size: usize) {
+        let old = self.used.fetch_sub(size, Ordering::Relaxed);
+        if let Some(parent) = self.parent {
+            parent.release(self.excess(old) - self.excess(old - size));
+        }
+        if let Some(watermarks) = &self.watermarks {
+            watermarks.update(old - size);
+        }
+    }
+
+    /// The part of `used` not covered by the reservation, which is charged to the parent
+    /// individually.
+    #[inline]
+    fn excess(&self, used: usize) -> usize {
+        used.saturating_sub(self.reserved)
+    }
+}
+
+impl Drop for Budget<'_> {
+    fn drop(&mut self) {
+        if let Some(parent) = self.parent {
+            parent.release(self.reserved + self.excess(self.used()));
+        }
+    }
+}
+
+/// An allocator wrapper that charges every block to a shared [`Budget`], failing
+/// allocations that would exceed it.
+#[derive(Debug, Clone)]
+pub struct Limited<'a, A> {
+    allocator: A,
+    budget: &'a Budget<'a>,
+}
+
+impl<'a, A> Limited<'a, A> {
+    pub const fn new(allocator: A, budget: &'a Budget<'a>) -> Self {
+        Self { allocator, budget }
+    }
+
+    pub fn budget(&self) -> &'a Budget<'a> {
+        self.budget
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+
+    /// Charge `size` bytes, run `f`, and return the charge if `f` fails.
+    #[inline]
+    fn charged<T>(
+        &self,
+        size: usize,
+        f: impl FnOnce() -> Result<T, AllocError>,
+    ) -> Result<T, AllocError> {
+        if !self.budget.try_charge(size) {
+            return Err(AllocError);
+        }
+        let result = f();
+        if result.is_err() {
+            self.budget.release(size);
+        }
+        result
+    }
+
+    /// Release the difference between two sizes once `f` has shrunk a block.
+    #[inline]
+    fn released<T>(
+        &self,
+        old_size: usize,
+        new_size: usize,
+        f: impl FnOnce() -> Result<T, AllocError>,
+    ) -> Result<T, AllocError> {
+        let result = f();
+        if result.is_ok() {
+            self.budget.release(old_size - new_size);
+        }
+        result
+    }
+}
+
+impl<A> Deallocator for Limited<'_, A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.allocator.deallocate(ptr, layout) };
+        self.budget.release(layout.size());
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        self.released(old_layout.size(), new_layout.size(), || unsafe {
+            self.allocator.try_shrink(ptr, old_layout, new_layout)
+        })
+    }
+}
+
+unsafe impl<A> Allocator for Limited<'_, A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.charged(layout.size(), || self.allocator.allocate(layout))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.charged(layout.size(), || self.allocator.allocate_zeroed(layout))
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.charged(layout.size(), || {
+            self.allocator.allocate_filled(layout, byte)
+        })
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.charged(new_layout.size() - old_layout.size(), || unsafe {
+            self.allocator.grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.charged(new_layout.size() - old_layout.size(), || unsafe {
+            self.allocator.grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.released(old_layout.size(), new_layout.size(), || unsafe {
+            self.allocator.shrink(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        self.charged(new_layout.size() - old_layout.size(), || unsafe {
+            self.allocator.try_grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        self.charged(new_layout.size() - old_layout.size(), || unsafe {
+            self.allocator.try_grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+}
+
+impl<A> Owns for Limited<'_, A>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A> Trim for Limited<'_, A>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
@@ -0,0 +1,206 @@
+use core::ptr::NonNull;
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::{GlobalStats, Trim};
+
+/// An allocator wrapper that keeps [`GlobalStats`] for the blocks allocated through it.
+///
+/// This collects the same statistics as [`WrapAsGlobal::with_stats`](crate::WrapAsGlobal),
+/// but for any allocator in a composed stack rather than only the global one.
+#[derive(Debug, Default)]
+pub struct Counted<A> {
+    allocator: A,
+    stats: GlobalStats,
+}
+
+impl<A> Counted<A> {
+    pub const fn new(allocator: A) -> Self {
+        Self {
+            allocator,
+            stats: GlobalStats::new(),
+        }
+    }
+
+    /// Create a wrapper whose statistics carry `label`, identifying them in a
+    /// [tree](GlobalStats::tree) report.
+    pub const fn with_label(allocator: A, label: &'static str) -> Self {
+        Self {
+            allocator,
+            stats: GlobalStats::with_label(label),
+        }
+    }
+
+    /// Create a wrapper that records into `stats`, which may be configured with a label
+    /// or [watermarks](GlobalStats::with_watermarks).
+    pub const fn with_stats(allocator: A, stats: GlobalStats) -> Self {
+        Self { allocator, stats }
+    }
+
+    pub fn stats(&self) -> &GlobalStats {
+        &self.stats
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+
+    #[inline]
+    fn allocated(
+        &self,
+        layout: NonZeroLayout,
+        result: Result<NonNull<u8>, AllocError>,
+    ) -> Result<NonNull<u8>, AllocError> {
+        if result.is_ok() {
+            self.stats.record_alloc(layout.size());
+        }
+        result
+    }
+
+    #[inline]
+    fn resized<T>(
+        &self,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+        result: Result<T, AllocError>,
+    ) -> Result<T, AllocError> {
+        if result.is_ok() {
+            self.stats
+                .record_realloc(old_layout.size(), new_layout.size());
+        }
+        result
+    }
+}
+
+impl<A> Deallocator for Counted<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.allocator.deallocate(ptr, layout) };
+        self.stats.record_dealloc(layout.size());
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        let result = unsafe { self.allocator.try_shrink(ptr, old_layout, new_layout) };
+        self.resized(old_layout, new_layout, result)
+    }
+}
+
+unsafe impl<A> Allocator for Counted<A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocated(layout, self.allocator.allocate(layout))
+    }
+
+    /// Reports exactly the requested size, without any slack from the wrapped allocator.
+    /// Callers may deallocate with any size between the requested and usable size, so
+    /// recording the usable size could leave bytes counted as live forever.
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.allocate(layout).map(|ptr| (ptr, layout.size()))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocated(layout, self.allocator.allocate_zeroed(layout))
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.allocated(layout, self.allocator.allocate_filled(layout, byte))
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let result = unsafe { self.allocator.grow(ptr, old_layout, new_layout) };
+        self.resized(old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let result = unsafe { self.allocator.grow_zeroed(ptr, old_layout, new_layout) };
+        self.resized(old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let result = unsafe { self.allocator.shrink(ptr, old_layout, new_layout) };
+        self.resized(old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        let result = unsafe { self.allocator.try_grow(ptr, old_layout, new_layout) };
+        self.resized(old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        let result = unsafe { self.allocator.try_grow_zeroed(ptr, old_layout, new_layout) };
+        self.resized(old_layout, new_layout, result)
+    }
+}
+
+impl<A> Owns for Counted<A>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A> Trim for Counted<A>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
@@ -0,0 +1,278 @@
+use core::{
+    cell::{Cell, UnsafeCell},
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+type Pending = (NonNull<u8>, NonZeroLayout);
+
+/// An allocator wrapper that postpones deallocations until [flush](Self::flush) is
+/// called.
+///
+/// Deallocated blocks are recorded in a queue of up to `N` entries, and released to
+/// the wrapped allocator in a batch when the queue is flushed. The queue is flushed
+/// automatically when it is full, when the number of pending bytes reaches the
+/// configured threshold, and when the wrapper is dropped.
+#[derive(Debug)]
+pub struct DeferredFree<A, const N: usize>
+where
+    A: Deallocator,
+{
+    allocator: A,
+    queue: UnsafeCell<[MaybeUninit<Pending>; N]>,
+    len: Cell<usize>,
+    pending_bytes: Cell<usize>,
+    threshold: usize,
+}
+
+impl<A, const N: usize> DeferredFree<A, N>
+where
+    A: Deallocator,
+{
+    pub const fn new(allocator: A) -> Self {
+        Self::with_threshold(allocator, usize::MAX)
+    }
+
+    /// Create a new wrapper that flushes automatically once at least `threshold` bytes
+    /// are pending.
+    pub const fn with_threshold(allocator: A, threshold: usize) -> Self {
+        Self {
+            allocator,
+            queue: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            len: Cell::new(0),
+            pending_bytes: Cell::new(0),
+            threshold,
+        }
+    }
+
+    /// Returns the number of deallocations waiting to be flushed.
+    pub fn pending(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Returns the total size of the blocks waiting to be flushed.
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes.get()
+    }
+
+    /// Release all pending deallocations to the wrapped allocator.
+    pub fn flush(&self) {
+        let len = self.len.replace(0);
+        self.pending_bytes.set(0);
+
+        for i in 0..len {
+            unsafe {
+                let (ptr, layout) = (*self.queue.get())[i].assume_init();
+                self.allocator.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.flush();
+        let this = ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.allocator) }
+    }
+}
+
+impl<A, const N: usize> Drop for DeferredFree<A, N>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<A, const N: usize> Deallocator for DeferredFree<A, N>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        if N == 0 {
+            unsafe { self.allocator.deallocate(ptr, layout) };
+            return;
+        }
+
+        if self.len.get() == N {
+            self.flush();
+        }
+
+        let len = self.len.get();
+        unsafe { (*self.queue.get())[len].write((ptr, layout)) };
+        self.len.set(len + 1);
+
+        let pending_bytes = self.pending_bytes.get().saturating_add(layout.size());
+        self.pending_bytes.set(pending_bytes);
+        if pending_bytes >= self.threshold {
+            self.flush();
+        }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe {
+            self.allocator
+                .try_shrink_at_least(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+unsafe impl<A, const N: usize> Allocator for DeferredFree<A, N>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.allocator.allocate_at_least(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_zeroed(layout)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_filled(layout, byte)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(NonNull<u8>, usize), AllocError> {
+        unsafe { self.allocator.grow_at_least(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.allocator.reallocate(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe {
+            self.allocator
+                .try_grow_at_least(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+impl<A, const N: usize> Owns for DeferredFree<A, N>
+where
+    A: Deallocator + Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A, const N: usize> Trim for DeferredFree<A, N>
+where
+    A: Deallocator + Trim,
+{
+    /// Flush all pending deallocations before trimming the wrapped allocator.
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        let flushed = self.pending() > 0;
+        self.flush();
+        self.allocator.trim(pad) || flushed
+    }
+}
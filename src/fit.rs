@@ -0,0 +1,193 @@
+use core::{fmt::Debug, ptr::NonNull};
+
+use divvy_core::NonZeroLayout;
+
+use crate::free_list::{FreeList, ALIGN, MIN_SIZE};
+
+mod sealed {
+    use core::ptr::NonNull;
+
+    use divvy_core::NonZeroLayout;
+
+    use crate::free_list::FreeList;
+
+    pub trait Strategy {
+        fn allocate(&mut self, list: &mut FreeList, layout: NonZeroLayout) -> Option<NonNull<u8>>;
+
+        /// # Safety
+        ///
+        /// See [`FreeList::deallocate`].
+        #[inline]
+        unsafe fn deallocate(
+            &mut self,
+            list: &mut FreeList,
+            ptr: NonNull<u8>,
+            layout: NonZeroLayout,
+        ) {
+            unsafe { list.deallocate(ptr, layout) }
+        }
+    }
+}
+
+/// How a [`Heap`](crate::Heap) chooses which free block serves a request.
+///
+/// The choice trades allocation speed against fragmentation, and which strategy works
+/// best depends on the workload:
+///
+/// - [`FirstFit`] takes the lowest-addressed block that fits. It is simple and fast
+///   for most workloads, but small leftover blocks tend to pile up at the start of the
+///   heap, slowing down later searches.
+/// - [`NextFit`] resumes the search where the previous allocation left off, spreading
+///   allocations across the heap. This keeps blocks allocated together close together,
+///   but usually leaves more, smaller free blocks behind than first-fit, which makes
+///   every later search and deallocation slower.
+/// - [`BestFit`] takes the smallest block that fits. It wastes the least memory, at
+///   the cost of searching the whole list on most allocations.
+/// - [`SegregatedFit`] keeps freed small blocks in bins of exactly one size, serving
+///   repeated small requests without searching, and falls back to first-fit otherwise.
+///
+/// This trait is sealed, and cannot be implemented outside of this crate.
+pub trait FitStrategy: sealed::Strategy + Debug {}
+
+/// Allocate from the first free block that fits. This is the default strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FirstFit;
+
+impl sealed::Strategy for FirstFit {
+    #[inline]
+    fn allocate(&mut self, list: &mut FreeList, layout: NonZeroLayout) -> Option<NonNull<u8>> {
+        list.first_fit(layout, 0..usize::MAX)
+    }
+}
+
+impl FitStrategy for FirstFit {}
+
+/// Allocate from the first free block that fits, starting after the previous
+/// allocation and wrapping around to the start of the heap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NextFit {
+    cursor: usize,
+}
+
+impl NextFit {
+    pub const fn new() -> Self {
+        Self { cursor: 0 }
+    }
+}
+
+impl sealed::Strategy for NextFit {
+    #[inline]
+    fn allocate(&mut self, list: &mut FreeList, layout: NonZeroLayout) -> Option<NonNull<u8>> {
+        let ptr = list
+            .first_fit(layout, self.cursor..usize::MAX)
+            .or_else(|| list.first_fit(layout, 0..self.cursor))?;
+        self.cursor = ptr.as_ptr().addr() + FreeList::block_size(layout);
+        Some(ptr)
+    }
+}
+
+impl FitStrategy for NextFit {}
+
+/// Allocate from the smallest free block that fits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BestFit;
+
+impl sealed::Strategy for BestFit {
+    #[inline]
+    fn allocate(&mut self, list: &mut FreeList, layout: NonZeroLayout) -> Option<NonNull<u8>> {
+        list.best_fit(layout)
+    }
+}
+
+impl FitStrategy for BestFit {}
+
+/// The number of exact-size bins kept by [`SegregatedFit`].
+const BINS: usize = 32;
+
+/// Keep freed small blocks in bins of a single size, and allocate from the first free
+/// block that fits when the matching bin is empty.
+///
+/// Blocks of up to 32 distinct sizes above the minimum block size are binned (up to
+/// 264 bytes on 64-bit targets). Binned blocks are not coalesced with their neighbours
+/// until the heap runs out of memory, at which point every bin is returned to the free
+/// list and the request is retried.
+#[derive(Debug, Default)]
+pub struct SegregatedFit {
+    bins: [Option<NonNull<Binned>>; BINS],
+}
+
+// The bins exclusively own the blocks they hold, just like the free list.
+unsafe impl Send for SegregatedFit {}
+
+#[derive(Debug)]
+struct Binned {
+    next: Option<NonNull<Binned>>,
+}
+
+impl SegregatedFit {
+    pub const fn new() -> Self {
+        Self { bins: [None; BINS] }
+    }
+
+    /// Returns the bin holding blocks of `size` bytes, if blocks of that size are
+    /// binned.
+    #[inline]
+    fn bin(size: usize) -> Option<usize> {
+        let bin = (size - MIN_SIZE) / ALIGN;
+        (bin < BINS).then_some(bin)
+    }
+
+    /// Return every binned block to the free list. Returns whether any were binned.
+    fn flush(&mut self, list: &mut FreeList) -> bool {
+        let mut flushed = false;
+        for (bin, head) in self.bins.iter_mut().enumerate() {
+            while let Some(block) = *head {
+                unsafe {
+                    *head = (*block.as_ptr()).next;
+                    list.insert(block.as_ptr().cast(), MIN_SIZE + bin * ALIGN);
+                }
+                flushed = true;
+            }
+        }
+        flushed
+    }
+}
+
+impl sealed::Strategy for SegregatedFit {
+    #[inline]
+    fn allocate(&mut self, list: &mut FreeList, layout: NonZeroLayout) -> Option<NonNull<u8>> {
+        // Binned blocks are only guaranteed the free list's own alignment.
+        if layout.align() <= ALIGN {
+            if let Some(bin) = Self::bin(FreeList::block_size(layout)) {
+                if let Some(block) = self.bins[bin] {
+                    self.bins[bin] = unsafe { (*block.as_ptr()).next };
+                    return Some(block.cast());
+                }
+            }
+        }
+
+        match list.first_fit(layout, 0..usize::MAX) {
+            Some(ptr) => Some(ptr),
+            None if self.flush(list) => list.first_fit(layout, 0..usize::MAX),
+            None => None,
+        }
+    }
+
+    #[inline]
+    unsafe fn deallocate(&mut self, list: &mut FreeList, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        let Some(bin) = Self::bin(FreeList::block_size(layout)) else {
+            unsafe { list.deallocate(ptr, layout) };
+            return;
+        };
+
+        let block = ptr.cast::<Binned>();
+        unsafe {
+            block.as_ptr().write(Binned {
+                next: self.bins[bin],
+            })
+        };
+        self.bins[bin] = Some(block);
+    }
+}
+
+impl FitStrategy for SegregatedFit {}
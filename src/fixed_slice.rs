@@ -5,9 +5,9 @@ use core::{
     ptr::{self, NonNull},
 };
 
-use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
 
-use crate::sub_ptr;
+use crate::{sub_ptr, Reset};
 
 #[derive(Debug)]
 pub struct FixedSlice<'a> {
@@ -44,6 +44,73 @@ impl<'a> FixedSlice<'a> {
         }
     }
 
+    /// Release all allocations, making the entire slice available again.
+    ///
+    /// Taking `&mut self` guarantees that nothing borrowing this allocator is still
+    /// alive. For resetting through a shared reference, see [`Reset`].
+    pub fn clear(&mut self) {
+        unsafe { Reset::reset(&*self) };
+    }
+
+    /// Returns the total size of the slice.
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the number of bytes that have been allocated, including padding.
+    pub fn used(&self) -> usize {
+        unsafe { sub_ptr(self.pos.get().as_ptr(), self.data.as_ptr().cast()) }
+    }
+
+    /// Returns the number of bytes that have yet to be allocated.
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.used()
+    }
+
+    /// Split the unallocated portion of the slice into two independent allocators, the
+    /// first managing the next `n` bytes and the second managing the rest.
+    ///
+    /// Blocks that have already been allocated remain valid, but can no longer be
+    /// deallocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the number of remaining bytes.
+    pub fn split_remaining(self, n: usize) -> (FixedSlice<'a>, FixedSlice<'a>) {
+        assert!(n <= self.remaining(), "split point out of bounds");
+
+        let start = self.pos.get().as_ptr();
+        let rest = self.remaining() - n;
+        unsafe {
+            let first = ptr::slice_from_raw_parts_mut(start, n);
+            let second = ptr::slice_from_raw_parts_mut(start.add(n), rest);
+            (Self::from_ptr_slice(first), Self::from_ptr_slice(second))
+        }
+    }
+
+    /// Split the unallocated portion of the slice into `N` independent allocators of
+    /// equal size. Any bytes left over from the division are given to the last one.
+    ///
+    /// Blocks that have already been allocated remain valid, but can no longer be
+    /// deallocated.
+    pub fn split_remaining_into<const N: usize>(self) -> [FixedSlice<'a>; N] {
+        let start = self.pos.get().as_ptr();
+        let remaining = self.remaining();
+        let part = remaining.checked_div(N).unwrap_or(0);
+
+        core::array::from_fn(|i| {
+            let len = if i + 1 == N {
+                remaining - part * i
+            } else {
+                part
+            };
+            unsafe {
+                let slice = ptr::slice_from_raw_parts_mut(start.add(part * i), len);
+                Self::from_ptr_slice(slice)
+            }
+        })
+    }
+
     /// Return a pointer to the portion of the slice that has yet to be allocated.
     pub fn unallocated_ptr(&self) -> NonNull<[u8]> {
         let data = self.pos.get().as_ptr();
@@ -58,9 +125,43 @@ impl<'a> FixedSlice<'a> {
     }
 }
 
+impl<'a> FixedSlice<'a> {
+    /// Returns `true` if the block is the most recent allocation, and therefore ends at
+    /// the current position.
+    #[inline]
+    fn is_last(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        ptr::eq(
+            ptr.as_ptr().wrapping_add(layout.size()),
+            self.pos.get().as_ptr(),
+        )
+    }
+}
+
 impl<'a> Deallocator for FixedSlice<'a> {
+    /// Deallocating the most recent allocation rewinds the slice so that its memory can
+    /// be reused. Deallocating any other block has no effect.
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        if self.is_last(ptr, layout) {
+            self.pos.set(ptr);
+        }
+    }
+
+    /// Shrinking always succeeds in place. If the block is the most recent allocation,
+    /// the freed tail becomes available again.
     #[inline]
-    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: divvy_core::NonZeroLayout) {}
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        if self.is_last(ptr, old_layout) {
+            let pos = unsafe { ptr.as_ptr().add(new_layout.size()) };
+            self.pos.set(unsafe { NonNull::new_unchecked(pos) });
+        }
+        Ok(())
+    }
 }
 
 unsafe impl<'a> Allocator for FixedSlice<'a> {
@@ -71,6 +172,47 @@ unsafe impl<'a> Allocator for FixedSlice<'a> {
         self.pos.set(bump_result.pos);
         Ok(bump_result.ptr)
     }
+
+    /// Growing succeeds in place if the block is the most recent allocation, is already
+    /// aligned for the new layout, and there is enough room left in the slice.
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        if !self.is_last(ptr, old_layout) || ptr.as_ptr().addr() & (new_layout.align() - 1) != 0 {
+            return Err(AllocError);
+        }
+
+        let extra = new_layout.size() - old_layout.size();
+        if extra > self.remaining() {
+            return Err(AllocError);
+        }
+
+        let pos = unsafe { self.pos.get().as_ptr().add(extra) };
+        self.pos.set(unsafe { NonNull::new_unchecked(pos) });
+        Ok(())
+    }
+}
+
+unsafe impl<'a> Reset for FixedSlice<'a> {
+    #[inline]
+    unsafe fn reset(&self) {
+        self.pos.set(self.data.cast());
+    }
+}
+
+impl<'a> Owns for FixedSlice<'a> {
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        let start = self.data.as_ptr().cast::<u8>().addr();
+        let end = start + self.data.len();
+        let addr = ptr.as_ptr().addr();
+
+        start <= addr && addr.checked_add(layout.size()).is_some_and(|e| e <= end)
+    }
 }
 
 #[derive(Debug)]
@@ -92,10 +234,10 @@ unsafe fn bump_alloc_impl(
         .checked_add(align_offset)?
         .checked_add(layout.size())?;
 
-    if end_offset < arena.len() {
+    if end_offset <= arena.len() {
         unsafe {
             let ptr = pos.add(align_offset);
-            let new_pos = pos.add(layout.size());
+            let new_pos = ptr.add(layout.size());
 
             let ptr = NonNull::new_unchecked(ptr);
             let new_pos = NonNull::new_unchecked(new_pos);
@@ -0,0 +1,219 @@
+use core::{
+    cmp, mem,
+    ops::Range,
+    ptr::{self, NonNull},
+};
+
+use divvy_core::NonZeroLayout;
+
+/// An address-ordered list of free blocks that coalesces adjacent blocks on
+/// deallocation. Which block serves a request is up to the heap's
+/// [fit strategy](crate::FitStrategy).
+///
+/// The list is stored intrusively within the free memory itself, so every block is at
+/// least `MIN_SIZE` bytes and aligned to `ALIGN`. Requests are rounded up accordingly.
+///
+/// The list is only public so that it can appear in the sealed strategy trait, and is
+/// not reachable from outside the crate.
+#[derive(Debug)]
+pub struct FreeList {
+    head: Hole,
+}
+
+// The list exclusively manages the memory its holes point into.
+unsafe impl Send for FreeList {}
+
+#[derive(Debug)]
+#[repr(C)]
+struct Hole {
+    size: usize,
+    next: Option<NonNull<Hole>>,
+}
+
+pub(crate) const MIN_SIZE: usize = mem::size_of::<Hole>();
+pub(crate) const ALIGN: usize = mem::align_of::<Hole>();
+
+impl FreeList {
+    pub(crate) const fn new() -> Self {
+        Self {
+            head: Hole {
+                size: 0,
+                next: None,
+            },
+        }
+    }
+
+    /// Add a region of memory to the list. Parts of the region that cannot hold an
+    /// aligned block are ignored.
+    ///
+    /// # Safety
+    ///
+    /// The region must be valid for reads and writes, must not overlap any memory
+    /// already managed by the list, and must remain valid for as long as the list is
+    /// used.
+    pub(crate) unsafe fn add_region(&mut self, start: *mut u8, size: usize) {
+        let offset = start.align_offset(ALIGN);
+        if offset > size {
+            return;
+        }
+
+        let size = (size - offset) & !(ALIGN - 1);
+        if size >= MIN_SIZE {
+            unsafe { self.insert(start.add(offset), size) };
+        }
+    }
+
+    /// Returns the size of the block that is used to satisfy `layout`.
+    #[inline]
+    pub(crate) fn block_size(layout: NonZeroLayout) -> usize {
+        let size = cmp::max(layout.size(), MIN_SIZE);
+        (size + ALIGN - 1) & !(ALIGN - 1)
+    }
+
+    /// Allocate from the first hole that fits `layout` and starts within `range`.
+    pub(crate) fn first_fit(
+        &mut self,
+        layout: NonZeroLayout,
+        range: Range<usize>,
+    ) -> Option<NonNull<u8>> {
+        let size = Self::block_size(layout);
+        let align = cmp::max(layout.align(), ALIGN);
+
+        let mut prev: *mut Hole = &mut self.head;
+        unsafe {
+            while let Some(hole) = (*prev).next {
+                let addr = hole.as_ptr().addr();
+                if addr >= range.end {
+                    break;
+                }
+                if addr >= range.start {
+                    if let Some(front) = Self::fit(hole, size, align) {
+                        return Some(Self::take(prev, hole, front, size));
+                    }
+                }
+                prev = hole.as_ptr();
+            }
+        }
+
+        None
+    }
+
+    /// Allocate from the smallest hole that fits `layout`, preferring lower addresses
+    /// among holes of the same size.
+    pub(crate) fn best_fit(&mut self, layout: NonZeroLayout) -> Option<NonNull<u8>> {
+        let size = Self::block_size(layout);
+        let align = cmp::max(layout.align(), ALIGN);
+
+        let mut best: Option<(*mut Hole, NonNull<Hole>, usize)> = None;
+        let mut prev: *mut Hole = &mut self.head;
+        unsafe {
+            while let Some(hole) = (*prev).next {
+                let hole_size = (*hole.as_ptr()).size;
+                let smaller = best.is_none_or(|(_, b, _)| hole_size < (*b.as_ptr()).size);
+                if smaller {
+                    if let Some(front) = Self::fit(hole, size, align) {
+                        best = Some((prev, hole, front));
+                        if hole_size == size {
+                            break;
+                        }
+                    }
+                }
+                prev = hole.as_ptr();
+            }
+
+            best.map(|(prev, hole, front)| Self::take(prev, hole, front, size))
+        }
+    }
+
+    /// Returns the offset within `hole` at which a block of `size` bytes aligned to
+    /// `align` can be placed, if the hole is large enough.
+    #[inline]
+    unsafe fn fit(hole: NonNull<Hole>, size: usize, align: usize) -> Option<usize> {
+        let start = hole.as_ptr().cast::<u8>();
+        let hole_size = unsafe { (*hole.as_ptr()).size };
+
+        // Padding before the block must itself be large enough to remain in the list as
+        // a hole.
+        let mut front = start.align_offset(align);
+        if front != 0 && front < MIN_SIZE {
+            front = MIN_SIZE + unsafe { start.add(MIN_SIZE) }.align_offset(align);
+        }
+
+        let end = front.saturating_add(size);
+        let back = hole_size.wrapping_sub(end);
+        (end <= hole_size && (back == 0 || back >= MIN_SIZE)).then_some(front)
+    }
+
+    /// Carve a block of `size` bytes out of `hole` at offset `front`, as returned by
+    /// [fit](Self::fit). `prev` must be the hole preceding `hole`.
+    #[inline]
+    unsafe fn take(prev: *mut Hole, hole: NonNull<Hole>, front: usize, size: usize) -> NonNull<u8> {
+        let start = hole.as_ptr().cast::<u8>();
+        let end = front + size;
+
+        unsafe {
+            let back = (*hole.as_ptr()).size - end;
+            let mut next = (*hole.as_ptr()).next;
+            if back != 0 {
+                let back_hole = start.add(end).cast::<Hole>();
+                back_hole.write(Hole { size: back, next });
+                next = Some(NonNull::new_unchecked(back_hole));
+            }
+
+            if front != 0 {
+                (*hole.as_ptr()).size = front;
+                (*hole.as_ptr()).next = next;
+            } else {
+                (*prev).next = next;
+            }
+
+            NonNull::new_unchecked(start.add(front))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The pointer must have been returned by a previous call to `allocate` on this list
+    /// with the same layout.
+    #[inline]
+    pub(crate) unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.insert(ptr.as_ptr(), Self::block_size(layout)) };
+    }
+
+    /// Return a block of `size` bytes to the list, coalescing it with its neighbours.
+    ///
+    /// # Safety
+    ///
+    /// The block must have been allocated from this list, and `size` must be its
+    /// [block size](Self::block_size).
+    pub(crate) unsafe fn insert(&mut self, start: *mut u8, mut size: usize) {
+        let head: *mut Hole = &mut self.head;
+        let mut prev = head;
+
+        unsafe {
+            while let Some(next) = (*prev).next {
+                if next.as_ptr().addr() > start.addr() {
+                    break;
+                }
+                prev = next.as_ptr();
+            }
+
+            let mut next = (*prev).next;
+            if let Some(hole) = next {
+                if ptr::eq(start.add(size), hole.as_ptr().cast()) {
+                    size += (*hole.as_ptr()).size;
+                    next = (*hole.as_ptr()).next;
+                }
+            }
+
+            if prev != head && ptr::eq(prev.cast::<u8>().add((*prev).size), start) {
+                (*prev).size += size;
+                (*prev).next = next;
+            } else {
+                let hole = start.cast::<Hole>();
+                hole.write(Hole { size, next });
+                (*prev).next = Some(NonNull::new_unchecked(hole));
+            }
+        }
+    }
+}
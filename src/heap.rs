@@ -0,0 +1,176 @@
+use core::ptr::NonNull;
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::{
+    fit::{FirstFit, FitStrategy},
+    free_list::FreeList,
+    spin_lock::SpinLock,
+};
+
+/// A general purpose heap over a region of memory provided at runtime.
+///
+/// The heap starts out empty, and must be given memory through [init](Heap::init)
+/// before it can satisfy any allocations. Memory is managed using a free list that
+/// coalesces freed blocks, behind a spin lock, so a `Heap` can be placed in a `static`
+/// and shared between threads.
+///
+/// Which free block serves each request is decided by the heap's
+/// [fit strategy](FitStrategy), which defaults to [first-fit](FirstFit):
+///
+/// ```ignore
+/// static HEAP: Heap<BestFit> = Heap::with_strategy(BestFit);
+/// ```
+#[derive(Debug)]
+pub struct Heap<S = FirstFit> {
+    state: SpinLock<HeapState<S>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct HeapState<S> {
+    list: FreeList,
+    strategy: S,
+    start: usize,
+    size: usize,
+    used: usize,
+    initialized: bool,
+}
+
+impl Heap {
+    /// Create a first-fit heap that has not yet been given any memory.
+    pub const fn empty() -> Self {
+        Self::with_strategy(FirstFit)
+    }
+}
+
+impl<S> Heap<S> {
+    /// Create a heap that places blocks using `strategy`, and has not yet been given
+    /// any memory.
+    pub const fn with_strategy(strategy: S) -> Self {
+        Self {
+            state: SpinLock::new(HeapState {
+                list: FreeList::new(),
+                strategy,
+                start: 0,
+                size: 0,
+                used: 0,
+                initialized: false,
+            }),
+        }
+    }
+
+    /// Give the heap a region of memory to allocate from.
+    ///
+    /// The free list only hands out blocks aligned to, and at least as large as, a free
+    /// list node (two pointers). Any unaligned bytes at either end of the region are left
+    /// unused, and a region too small to hold a single node leaves the heap initialized
+    /// but unable to serve any allocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the heap has already been initialized.
+    ///
+    /// # Safety
+    ///
+    /// The region must be valid for reads and writes, must not be used for anything
+    /// else, and must remain valid for as long as the heap is used.
+    pub unsafe fn init(&self, start: *mut u8, size: usize) {
+        self.with_state(|state| {
+            assert!(!state.is_initialized(), "heap is already initialized");
+            unsafe { state.init(start, size) };
+        })
+    }
+
+    /// Returns the size of the region managed by the heap.
+    pub fn size(&self) -> usize {
+        self.with_state(|state| state.size)
+    }
+
+    /// Returns the number of bytes currently allocated, including padding added to
+    /// satisfy the free list's minimum block size.
+    pub fn used(&self) -> usize {
+        self.with_state(|state| state.used)
+    }
+
+    /// Returns the number of bytes not currently allocated.
+    pub fn free(&self) -> usize {
+        self.with_state(|state| state.size - state.used)
+    }
+
+    #[inline]
+    pub(crate) fn with_state<R>(&self, f: impl FnOnce(&mut HeapState<S>) -> R) -> R {
+        self.state.with(f)
+    }
+}
+
+impl<S> HeapState<S> {
+    #[inline]
+    pub(crate) fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// # Safety
+    ///
+    /// See [`Heap::init`].
+    pub(crate) unsafe fn init(&mut self, start: *mut u8, size: usize) {
+        unsafe { self.list.add_region(start, size) };
+        self.start = start.addr();
+        self.size = size;
+        self.initialized = true;
+    }
+}
+
+impl<S> Default for Heap<S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_strategy(S::default())
+    }
+}
+
+impl<S> Deallocator for Heap<S>
+where
+    S: FitStrategy,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        self.with_state(|state| {
+            unsafe { state.strategy.deallocate(&mut state.list, ptr, layout) };
+            state.used -= FreeList::block_size(layout);
+        })
+    }
+}
+
+unsafe impl<S> Allocator for Heap<S>
+where
+    S: FitStrategy,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.with_state(|state| {
+            let ptr = state
+                .strategy
+                .allocate(&mut state.list, layout)
+                .ok_or(AllocError)?;
+            state.used += FreeList::block_size(layout);
+            Ok(ptr)
+        })
+    }
+}
+
+impl<S> Owns for Heap<S>
+where
+    S: FitStrategy,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        let (start, size) = self.with_state(|state| (state.start, state.size));
+        let addr = ptr.as_ptr().addr();
+
+        start <= addr
+            && addr
+                .checked_add(layout.size())
+                .is_some_and(|end| end <= start + size)
+    }
+}
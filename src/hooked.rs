@@ -0,0 +1,249 @@
+use core::ptr::NonNull;
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+/// An operation performed through a [`Hooked`] allocator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocEvent {
+    /// A block was allocated.
+    Allocate {
+        ptr: NonNull<u8>,
+        layout: NonZeroLayout,
+    },
+    /// A block was deallocated.
+    Deallocate {
+        ptr: NonNull<u8>,
+        layout: NonZeroLayout,
+    },
+    /// A block was grown, possibly moving it to `new_ptr`.
+    Grow {
+        old_ptr: NonNull<u8>,
+        new_ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    },
+    /// A block was shrunk, possibly moving it to `new_ptr`.
+    Shrink {
+        old_ptr: NonNull<u8>,
+        new_ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    },
+    /// An allocation, grow, or shrink failed. `layout` is the requested layout.
+    Failed { layout: NonZeroLayout },
+}
+
+/// An allocator wrapper that calls a user-provided hook for every operation.
+///
+/// The hook is called after each operation completes, with an [`AllocEvent`]
+/// describing it. Failed attempts to grow or shrink in place are not reported, since
+/// they leave the block unchanged.
+#[derive(Debug, Default, Clone)]
+pub struct Hooked<A, F> {
+    allocator: A,
+    hook: F,
+}
+
+impl<A, F> Hooked<A, F> {
+    pub const fn new(allocator: A, hook: F) -> Self {
+        Self { allocator, hook }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+}
+
+impl<A, F> Hooked<A, F>
+where
+    F: Fn(AllocEvent),
+{
+    #[inline]
+    fn allocated<T>(
+        &self,
+        layout: NonZeroLayout,
+        result: Result<T, AllocError>,
+        ptr: impl FnOnce(&T) -> NonNull<u8>,
+    ) -> Result<T, AllocError> {
+        match &result {
+            Ok(value) => (self.hook)(AllocEvent::Allocate {
+                ptr: ptr(value),
+                layout,
+            }),
+            Err(AllocError) => (self.hook)(AllocEvent::Failed { layout }),
+        }
+        result
+    }
+
+    #[inline]
+    fn resized(
+        &self,
+        old_ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+        result: Result<NonNull<u8>, AllocError>,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let event = match result {
+            Ok(new_ptr) if new_layout.size() >= old_layout.size() => AllocEvent::Grow {
+                old_ptr,
+                new_ptr,
+                old_layout,
+                new_layout,
+            },
+            Ok(new_ptr) => AllocEvent::Shrink {
+                old_ptr,
+                new_ptr,
+                old_layout,
+                new_layout,
+            },
+            Err(AllocError) => AllocEvent::Failed { layout: new_layout },
+        };
+        (self.hook)(event);
+        result
+    }
+}
+
+impl<A, F> Deallocator for Hooked<A, F>
+where
+    A: Deallocator,
+    F: Fn(AllocEvent),
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.allocator.deallocate(ptr, layout) };
+        (self.hook)(AllocEvent::Deallocate { ptr, layout });
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_shrink(ptr, old_layout, new_layout)? };
+        (self.hook)(AllocEvent::Shrink {
+            old_ptr: ptr,
+            new_ptr: ptr,
+            old_layout,
+            new_layout,
+        });
+        Ok(())
+    }
+}
+
+unsafe impl<A, F> Allocator for Hooked<A, F>
+where
+    A: Allocator,
+    F: Fn(AllocEvent),
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocated(layout, self.allocator.allocate(layout), |p| *p)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.allocated(layout, self.allocator.allocate_at_least(layout), |p| p.0)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocated(layout, self.allocator.allocate_zeroed(layout), |p| *p)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.allocated(layout, self.allocator.allocate_filled(layout, byte), |p| *p)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let result = unsafe { self.allocator.grow(ptr, old_layout, new_layout) };
+        self.resized(ptr, old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let result = unsafe { self.allocator.grow_zeroed(ptr, old_layout, new_layout) };
+        self.resized(ptr, old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let result = unsafe { self.allocator.shrink(ptr, old_layout, new_layout) };
+        self.resized(ptr, old_layout, new_layout, result)
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.allocator.try_grow(ptr, old_layout, new_layout)? };
+        self.resized(ptr, old_layout, new_layout, Ok(ptr))?;
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            self.allocator
+                .try_grow_zeroed(ptr, old_layout, new_layout)?
+        };
+        self.resized(ptr, old_layout, new_layout, Ok(ptr))?;
+        Ok(())
+    }
+}
+
+impl<A, F> Owns for Hooked<A, F>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A, F> Trim for Hooked<A, F>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
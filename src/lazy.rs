@@ -0,0 +1,316 @@
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+const UNINIT: u8 = 0;
+const RUNNING: u8 = 1;
+const READY: u8 = 2;
+const POISONED: u8 = 3;
+
+/// An allocator that is constructed on first use.
+///
+/// This allows allocators whose construction is expensive or cannot be done in a `const`
+/// context, such as those that reserve memory from the operating system, to be declared
+/// in a `static`:
+///
+/// ```ignore
+/// static POOL: Lazy<Pool> = Lazy::new(|| Pool::with_capacity(1 << 20));
+/// ```
+///
+/// If several threads race to allocate first, one of them runs the constructor while the
+/// others wait for it. A constructor that panics poisons the wrapper, and every later
+/// use panics as well.
+pub struct Lazy<A, F = fn() -> A> {
+    state: AtomicU8,
+    init: UnsafeCell<Option<F>>,
+    allocator: UnsafeCell<MaybeUninit<A>>,
+}
+
+unsafe impl<A, F> Send for Lazy<A, F>
+where
+    A: Send,
+    F: Send,
+{
+}
+
+unsafe impl<A, F> Sync for Lazy<A, F>
+where
+    A: Send + Sync,
+    F: Send,
+{
+}
+
+impl<A, F> Lazy<A, F>
+where
+    F: FnOnce() -> A,
+{
+    pub const fn new(init: F) -> Self {
+        Self {
+            state: AtomicU8::new(UNINIT),
+            init: UnsafeCell::new(Some(init)),
+            allocator: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Returns the allocator, constructing it first if needed.
+    #[inline]
+    pub fn force(this: &Lazy<A, F>) -> &A {
+        match Lazy::get(this) {
+            Some(allocator) => allocator,
+            None => Lazy::force_slow(this),
+        }
+    }
+
+    #[cold]
+    fn force_slow(this: &Lazy<A, F>) -> &A {
+        loop {
+            match this.state.compare_exchange_weak(
+                UNINIT,
+                RUNNING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(READY) => return unsafe { Lazy::get_unchecked(this) },
+                Err(POISONED) => panic!("Lazy allocator constructor panicked"),
+                Err(_) => wait(),
+            }
+        }
+
+        // Poisons the wrapper if the constructor panics, so that waiting threads do not
+        // spin forever.
+        struct Poison<'a>(&'a AtomicU8);
+
+        impl Drop for Poison<'_> {
+            fn drop(&mut self) {
+                self.0.store(POISONED, Ordering::Release);
+            }
+        }
+
+        let poison = Poison(&this.state);
+        let init = unsafe { (*this.init.get()).take() }.unwrap();
+        unsafe { (*this.allocator.get()).write(init()) };
+        core::mem::forget(poison);
+
+        this.state.store(READY, Ordering::Release);
+        unsafe { Lazy::get_unchecked(this) }
+    }
+}
+
+impl<A, F> Lazy<A, F> {
+    /// Returns the allocator if it has been constructed.
+    #[inline]
+    pub fn get(this: &Lazy<A, F>) -> Option<&A> {
+        if this.state.load(Ordering::Acquire) == READY {
+            Some(unsafe { Lazy::get_unchecked(this) })
+        } else {
+            None
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The allocator must have been constructed.
+    #[inline]
+    unsafe fn get_unchecked(this: &Lazy<A, F>) -> &A {
+        unsafe { (*this.allocator.get()).assume_init_ref() }
+    }
+}
+
+#[inline]
+fn wait() {
+    #[cfg(feature = "std")]
+    std::thread::yield_now();
+    #[cfg(not(feature = "std"))]
+    core::hint::spin_loop();
+}
+
+impl<A, F> Drop for Lazy<A, F> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == READY {
+            unsafe { self.allocator.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+impl<A, F> Debug for Lazy<A, F>
+where
+    A: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut d = f.debug_tuple("Lazy");
+        match Lazy::get(self) {
+            Some(allocator) => d.field(allocator),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+impl<A, F> Deallocator for Lazy<A, F>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        // A block can only have been allocated after the allocator was constructed.
+        unsafe { Lazy::get_unchecked(self).deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { Lazy::get_unchecked(self).try_shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe { Lazy::get_unchecked(self).try_shrink_at_least(ptr, old_layout, new_layout) }
+    }
+}
+
+unsafe impl<A, F> Allocator for Lazy<A, F>
+where
+    A: Allocator,
+    F: FnOnce() -> A,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        Lazy::force(self).allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        Lazy::force(self).allocate_at_least(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        Lazy::force(self).allocate_zeroed(layout)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        Lazy::force(self).allocate_filled(layout, byte)
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { Lazy::get_unchecked(self).grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(NonNull<u8>, usize), AllocError> {
+        unsafe { Lazy::get_unchecked(self).grow_at_least(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { Lazy::get_unchecked(self).grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { Lazy::get_unchecked(self).shrink(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { Lazy::get_unchecked(self).reallocate(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { Lazy::get_unchecked(self).try_grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { Lazy::get_unchecked(self).try_grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn try_grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        unsafe { Lazy::get_unchecked(self).try_grow_at_least(ptr, old_layout, new_layout) }
+    }
+}
+
+impl<A, F> Owns for Lazy<A, F>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        Lazy::get(self).is_some_and(|allocator| allocator.owns(ptr, layout))
+    }
+}
+
+impl<A, F> Trim for Lazy<A, F>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        Lazy::get(self).is_some_and(|allocator| allocator.trim(pad))
+    }
+}
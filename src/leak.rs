@@ -0,0 +1,109 @@
+use core::ptr::NonNull;
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+/// An allocator wrapper that never deallocates.
+///
+/// Calls to `deallocate` are ignored, and the memory is leaked. Allocations are
+/// forwarded to the wrapped allocator, which never sees a block again once it has
+/// returned it: shrinking always succeeds in place without copying, and growing copies
+/// the data into a new block and leaks the old one.
+#[derive(Debug, Default, Clone)]
+pub struct Leak<A> {
+    allocator: A,
+}
+
+impl<A> Leak<A> {
+    pub const fn new(allocator: A) -> Self {
+        Self { allocator }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+}
+
+impl<A> Deallocator for Leak<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: NonZeroLayout) {}
+
+    /// Shrinking always succeeds in place. The tail of the block is leaked along with
+    /// the rest of it, so there is nothing to give back.
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: NonZeroLayout,
+        _new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        Ok(new_layout.size())
+    }
+}
+
+unsafe impl<A> Allocator for Leak<A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        self.allocator.allocate_at_least(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_zeroed(layout)
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        self.allocator.allocate_filled(layout, byte)
+    }
+}
+
+impl<A> Owns for Leak<A>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A> Trim for Leak<A>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
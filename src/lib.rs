@@ -1,20 +1,91 @@
 #![no_std]
+#![cfg_attr(
+    feature = "strict_provenance",
+    feature(strict_provenance_lints),
+    deny(fuzzy_provenance_casts, lossy_provenance_casts)
+)]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
 pub use divvy_core::*;
+#[cfg(feature = "derive")]
+pub use divvy_derive::{Allocator, Deallocator, Owns, Trim};
 
+pub use crate::{
+    align_to::AlignTo,
+    allocation::Allocation,
+    at_most::{AtMost, Once},
+    budget::{Budget, Limited},
+    counted::Counted,
+    deferred_free::DeferredFree,
+    fit::{BestFit, FirstFit, FitStrategy, NextFit, SegregatedFit},
+    fixed_slice::FixedSlice,
+    heap::Heap,
+    hooked::{AllocEvent, Hooked},
+    lazy::Lazy,
+    leak::Leak,
+    never::Never,
+    owned_slice::OwnedSlice,
+    panic_on_alloc::PanicOnAlloc,
+    reset::Reset,
+    size_class_cache::{SizeClassCache, DEFAULT_SIZE_CLASSES},
+    static_heap::StaticHeap,
+    stats::{GlobalStats, StatsChildren, StatsSnapshot, StatsTree},
+    tagged::Tagged,
+    trim::Trim,
+    watermark::{Watermark, Watermarks},
+    wrap_as_global::{OomAction, WrapAsGlobal},
+};
 #[cfg(feature = "alloc")]
-pub use crate::global::{Global, WrapAsGlobal};
-pub use crate::{fixed_slice::FixedSlice, never::Never};
+pub use crate::{allocator_ext::AllocatorExt, any_allocator::AnyAllocator, global::Global};
+#[cfg(unix)]
+pub use crate::{sbrk::Sbrk, secure::Secure};
 
+mod align_to;
+mod allocation;
+#[cfg(feature = "alloc")]
+mod allocator_ext;
+#[cfg(feature = "alloc")]
+mod any_allocator;
+mod at_most;
+mod budget;
+mod counted;
+mod deferred_free;
+mod fit;
 mod fixed_slice;
+mod free_list;
 #[cfg(feature = "alloc")]
 mod global;
+mod heap;
+mod hooked;
+mod lazy;
+mod leak;
 mod never;
+mod owned_slice;
+mod panic_on_alloc;
+#[cfg(feature = "std")]
+pub mod pressure;
+#[cfg(feature = "alloc")]
+pub mod registry;
+mod reset;
+#[cfg(unix)]
+mod sbrk;
+#[cfg(unix)]
+mod secure;
+mod size_class_cache;
+mod spin_lock;
+mod static_heap;
+mod stats;
+#[cfg(feature = "nightly")]
+mod std_alloc;
+mod tagged;
+mod trim;
+mod watermark;
+mod wrap_as_global;
 
 #[inline]
 unsafe fn sub_ptr<T>(left: *const T, right: *const T) -> usize {
-    (left as usize) - (right as usize)
+    unsafe { left.offset_from(right) as usize }
 }
@@ -1,6 +1,6 @@
 use core::ptr::NonNull;
 
-use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
 
 #[derive(Debug, Default, Clone)]
 pub struct Never;
@@ -18,3 +18,10 @@ unsafe impl Allocator for Never {
         Err(AllocError)
     }
 }
+
+impl Owns for Never {
+    #[inline]
+    fn owns(&self, _ptr: NonNull<u8>, _layout: NonZeroLayout) -> bool {
+        false
+    }
+}
@@ -0,0 +1,163 @@
+use core::{mem::ManuallyDrop, ptr::NonNull};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+#[cfg(feature = "alloc")]
+use crate::Global;
+use crate::{FixedSlice, Reset};
+
+/// A bump allocator over a buffer that it owns.
+///
+/// The buffer is allocated from another allocator when the `OwnedSlice` is created,
+/// and returned to it when the `OwnedSlice` is dropped. This behaves exactly like a
+/// [`FixedSlice`], but without a borrowed lifetime, making it easy to store in
+/// long-lived structs.
+#[derive(Debug)]
+pub struct OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    // The slice borrows from the buffer, which is only freed after the slice is dropped.
+    slice: ManuallyDrop<FixedSlice<'static>>,
+    ptr: NonNull<u8>,
+    layout: Option<NonZeroLayout>,
+    allocator: A,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedSlice<Global> {
+    /// Create a new bump allocator with a buffer of `size` bytes allocated from the
+    /// global allocator.
+    pub fn new(size: usize) -> Self {
+        Self::new_in(size, Global)
+    }
+}
+
+impl<A> OwnedSlice<A>
+where
+    A: Allocator,
+{
+    /// Create a new bump allocator with a buffer of `size` bytes allocated from
+    /// `allocator`.
+    pub fn try_new_in(size: usize, allocator: A) -> Result<Self, AllocError> {
+        let layout = NonZeroLayout::from_size_align(size, 1);
+        let ptr = match layout {
+            Some(layout) => allocator.allocate(layout)?,
+            None => NonNull::dangling(),
+        };
+
+        let data = core::ptr::slice_from_raw_parts_mut(ptr.as_ptr(), size);
+        Ok(Self {
+            slice: ManuallyDrop::new(unsafe { FixedSlice::from_ptr_slice(data) }),
+            ptr,
+            layout,
+            allocator,
+        })
+    }
+
+    pub fn new_in(size: usize, allocator: A) -> Self {
+        Self::try_new_in(size, allocator).expect("allocation failed")
+    }
+}
+
+impl<A> OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    /// Release all allocations, making the entire buffer available again.
+    pub fn clear(&mut self) {
+        self.slice.clear();
+    }
+
+    /// Returns the total size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.slice.capacity()
+    }
+
+    /// Returns the number of bytes that have been allocated, including padding.
+    pub fn used(&self) -> usize {
+        self.slice.used()
+    }
+
+    /// Returns the number of bytes that have yet to be allocated.
+    pub fn remaining(&self) -> usize {
+        self.slice.remaining()
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.allocator
+    }
+}
+
+impl<A> Drop for OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.slice) };
+
+        if let Some(layout) = self.layout {
+            unsafe { self.allocator.deallocate(self.ptr, layout) };
+        }
+    }
+}
+
+impl<A> Deallocator for OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.slice.deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.slice.try_shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+unsafe impl<A> Allocator for OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.slice.allocate(layout)
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.slice.try_grow(ptr, old_layout, new_layout) }
+    }
+}
+
+unsafe impl<A> Reset for OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn reset(&self) {
+        unsafe { Reset::reset(&*self.slice) }
+    }
+}
+
+impl<A> Owns for OwnedSlice<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.slice.owns(ptr, layout)
+    }
+}
@@ -0,0 +1,32 @@
+use core::ptr::NonNull;
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+/// An allocator that panics on any attempt to allocate.
+///
+/// Unlike [`Never`](crate::Never), which reports failure through an `AllocError`,
+/// this gives a loud, immediate failure that includes the offending layout. This is
+/// useful for tracking down unexpected allocations.
+#[derive(Debug, Default, Clone)]
+pub struct PanicOnAlloc;
+
+impl Deallocator for PanicOnAlloc {
+    #[inline]
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, layout: NonZeroLayout) {
+        panic!("unexpected deallocation with {:?}", layout.get());
+    }
+}
+
+unsafe impl Allocator for PanicOnAlloc {
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        panic!("unexpected allocation with {:?}", layout.get());
+    }
+}
+
+impl Owns for PanicOnAlloc {
+    #[inline]
+    fn owns(&self, _ptr: NonNull<u8>, _layout: NonZeroLayout) -> bool {
+        false
+    }
+}
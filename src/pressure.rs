@@ -0,0 +1,236 @@
+//! Reacting to system memory pressure.
+//!
+//! Allocators that hold on to memory, such as long-lived arenas and pools, can be
+//! registered here so that they are [trimmed](Trim) when the system runs low on memory.
+//! Arbitrary callbacks can be registered as well.
+//!
+//! Pressure is reported either by calling [notify] directly, for example from a
+//! platform-specific handler, or by [spawning a monitor](spawn_monitor) that watches the
+//! operating system. The monitor currently supports Linux pressure stall information,
+//! through `/proc/pressure/memory` or a cgroup's `memory.pressure` file.
+//!
+//! ```ignore
+//! static POOL: MyPool = MyPool::new();
+//!
+//! divvy::pressure::register_trim(&POOL);
+//! divvy::pressure::register_hook(|_| texture_cache().clear());
+//! divvy::pressure::spawn_monitor(MonitorConfig::default())?.detach();
+//! ```
+
+extern crate std;
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+use crate::Trim;
+
+/// How severe the reported memory pressure is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Pressure {
+    /// Some tasks are stalled waiting for memory. Caches should be shed.
+    Moderate,
+    /// All tasks are stalled waiting for memory. Everything that can be released should
+    /// be.
+    Critical,
+}
+
+type Hook = Arc<dyn Fn(Pressure) + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct Registry {
+    trims: Vec<&'static (dyn Trim + Sync)>,
+    hooks: Vec<Hook>,
+}
+
+/// The registry is replaced rather than modified, so that [notify] can take a snapshot
+/// without allocating and run every callback without holding the lock.
+static REGISTRY: Mutex<Option<Arc<Registry>>> = Mutex::new(None);
+
+fn lock() -> MutexGuard<'static, Option<Arc<Registry>>> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn update(f: impl FnOnce(&mut Registry)) {
+    let mut registry = lock();
+    let mut next = registry.as_deref().cloned().unwrap_or_default();
+    f(&mut next);
+    *registry = Some(Arc::new(next));
+}
+
+/// Register an allocator to be trimmed whenever pressure is reported.
+pub fn register_trim(allocator: &'static (dyn Trim + Sync)) {
+    update(|registry| registry.trims.push(allocator));
+}
+
+/// Register a callback to be run whenever pressure is reported.
+pub fn register_hook<F>(hook: F)
+where
+    F: Fn(Pressure) + Send + Sync + 'static,
+{
+    update(|registry| registry.hooks.push(Arc::new(hook)));
+}
+
+/// Report memory pressure, trimming every registered allocator and running every
+/// registered callback, in registration order. Returns `true` if any allocator released
+/// memory.
+///
+/// Allocators are trimmed as far as possible, with no padding, whatever the level of
+/// pressure; callbacks can use the level to decide how much to release. Callbacks run
+/// without any lock held, so they may register further allocators or callbacks, which
+/// take effect from the next report.
+pub fn notify(pressure: Pressure) -> bool {
+    let Some(registry) = lock().clone() else {
+        return false;
+    };
+    let mut released = false;
+    for allocator in &registry.trims {
+        released |= allocator.trim(0);
+    }
+    for hook in &registry.hooks {
+        hook(pressure);
+    }
+    released
+}
+
+/// Configuration for [spawn_monitor].
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    /// The pressure stall information file to watch.
+    pub path: PathBuf,
+    /// How often the file is read.
+    pub interval: Duration,
+    /// The percentage of time, averaged over ten seconds, during which some tasks are
+    /// stalled on memory at or above which [`Pressure::Moderate`] is reported.
+    pub moderate: f32,
+    /// The percentage of time, averaged over ten seconds, during which all tasks are
+    /// stalled on memory at or above which [`Pressure::Critical`] is reported.
+    pub critical: f32,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("/proc/pressure/memory"),
+            interval: Duration::from_secs(1),
+            moderate: 10.0,
+            critical: 5.0,
+        }
+    }
+}
+
+/// Spawn a background thread that watches the operating system for memory pressure and
+/// calls [notify] whenever it is detected, at most once per interval.
+///
+/// The thread runs until the returned [`Monitor`] is stopped or dropped, or for the
+/// rest of the program if it is [detached](Monitor::detach).
+///
+/// Fails with [`io::ErrorKind::Unsupported`] on platforms without a supported pressure
+/// source, or with the underlying error if the pressure file cannot be read.
+pub fn spawn_monitor(config: MonitorConfig) -> io::Result<Monitor> {
+    if !cfg!(target_os = "linux") {
+        return Err(io::Error::from(io::ErrorKind::Unsupported));
+    }
+
+    read_psi(&config)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    let thread = thread::Builder::new()
+        .name("divvy-pressure".into())
+        .spawn(move || loop {
+            // Parking rather than sleeping lets a stop request wake the thread early.
+            let deadline = Instant::now() + config.interval;
+            while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+                if stopped.load(Ordering::Acquire) {
+                    return;
+                }
+                thread::park_timeout(timeout);
+            }
+            if stopped.load(Ordering::Acquire) {
+                return;
+            }
+            if let Ok(Some(pressure)) = read_psi(&config) {
+                notify(pressure);
+            }
+        })?;
+
+    Ok(Monitor {
+        stop,
+        thread: Some(thread),
+    })
+}
+
+/// A handle to a thread started by [spawn_monitor]. Dropping it stops the thread and
+/// waits for it to finish.
+#[derive(Debug)]
+pub struct Monitor {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Monitor {
+    /// Stop the monitor and wait for its thread to finish.
+    pub fn stop(self) {
+        drop(self);
+    }
+
+    /// Let the monitor run for the rest of the program.
+    pub fn detach(mut self) {
+        self.thread = None;
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stop.store(true, Ordering::Release);
+            thread.thread().unpark();
+            // A panic in the thread has already been reported, so it is ignored here.
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Read a pressure stall information file, of the form
+///
+/// ```text
+/// some avg10=0.00 avg60=0.00 avg300=0.00 total=0
+/// full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+/// ```
+fn read_psi(config: &MonitorConfig) -> io::Result<Option<Pressure>> {
+    let contents = fs::read_to_string(&config.path)?;
+    let mut some = 0.0;
+    let mut full = 0.0;
+
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let avg10 = fields
+            .find_map(|field| field.strip_prefix("avg10="))
+            .and_then(|value| value.parse::<f32>().ok());
+        let Some(avg10) = avg10 else {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        };
+        match kind {
+            Some("some") => some = avg10,
+            Some("full") => full = avg10,
+            _ => {}
+        }
+    }
+
+    Ok(if full >= config.critical {
+        Some(Pressure::Critical)
+    } else if some >= config.moderate {
+        Some(Pressure::Moderate)
+    } else {
+        None
+    })
+}
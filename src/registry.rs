@@ -0,0 +1,52 @@
+//! A process-wide registry of named allocator statistics.
+//!
+//! Registering is opt-in. Subsystems register the [`GlobalStats`] of their allocators
+//! under a name, and [snapshot] returns the current figures for all of them at once,
+//! which is convenient for building memory overview screens.
+//!
+//! ```ignore
+//! static RENDER_ARENA: Counted<Heap> = Counted::new(Heap::empty());
+//!
+//! divvy::registry::register("render_arena", RENDER_ARENA.stats());
+//!
+//! for (name, stats) in divvy::registry::snapshot() {
+//!     println!("{name}: {} bytes live", stats.live_bytes);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{spin_lock::SpinLock, GlobalStats, StatsSnapshot};
+
+static REGISTRY: SpinLock<Vec<(&'static str, &'static GlobalStats)>> = SpinLock::new(Vec::new());
+
+/// Register `stats` under `name`, replacing any statistics previously registered under
+/// the same name.
+///
+/// Registering allocates, so this must not be called from within the global allocator.
+pub fn register(name: &'static str, stats: &'static GlobalStats) {
+    REGISTRY.with(|entries| {
+        if let Some(entry) = entries.iter_mut().find(|(n, _)| *n == name) {
+            entry.1 = stats;
+        } else {
+            entries.push((name, stats));
+        }
+    });
+}
+
+/// Remove the statistics registered under `name`, returning them if there were any.
+pub fn unregister(name: &str) -> Option<&'static GlobalStats> {
+    REGISTRY.with(|entries| {
+        let index = entries.iter().position(|(n, _)| *n == name)?;
+        Some(entries.remove(index).1)
+    })
+}
+
+/// Return the current statistics for every registered allocator, in registration order.
+pub fn snapshot() -> Vec<(&'static str, StatsSnapshot)> {
+    let entries = REGISTRY.with(|entries| entries.clone());
+    entries
+        .into_iter()
+        .map(|(name, stats)| (name, stats.snapshot()))
+        .collect()
+}
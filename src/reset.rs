@@ -0,0 +1,27 @@
+/// An allocator that can release all of its allocations at once.
+///
+/// # Safety
+///
+/// Implementations must ensure that after a call to `reset`, the allocator continues
+/// to uphold the [`Allocator`](divvy_core::Allocator) contract for any new
+/// allocations, even if they reuse memory from before the reset.
+pub unsafe trait Reset {
+    /// Release all allocations made by this allocator, making their memory available
+    /// to be allocated again.
+    ///
+    /// # Safety
+    ///
+    /// All blocks previously returned by this allocator are invalidated. The caller
+    /// must ensure that none of them are used or deallocated after this call.
+    unsafe fn reset(&self);
+}
+
+unsafe impl<A> Reset for &A
+where
+    A: Reset + ?Sized,
+{
+    #[inline]
+    unsafe fn reset(&self) {
+        unsafe { (**self).reset() }
+    }
+}
@@ -0,0 +1,144 @@
+use core::{
+    ffi::c_void,
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout};
+
+use crate::spin_lock::SpinLock;
+
+extern "C" {
+    fn sbrk(increment: isize) -> *mut c_void;
+}
+
+/// Serializes this crate's use of the program break. Other users of `sbrk`, such as the
+/// system `malloc`, are not covered, which is why every call re-checks the break.
+static LOCK: SpinLock<()> = SpinLock::new(());
+
+/// An allocator that extends the program break with `sbrk`.
+///
+/// This is about the simplest possible backend, intended for constrained unix-like
+/// environments and as a base for custom heaps. Blocks are carved from the end of the
+/// data segment one after another. Only the most recent block can be given back:
+/// deallocating or shrinking it lowers the break again, while deallocating any other
+/// block does nothing and leaks it. Likewise, only the most recent block can grow in
+/// place.
+///
+/// On most platforms the system `malloc` also moves the break. The two can coexist, since
+/// the break is only moved back when it still ends exactly at the block being released,
+/// but memory freed below a `malloc` allocation is never reclaimed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sbrk;
+
+/// Move the break by `increment` bytes, returning the previous break.
+#[inline]
+unsafe fn brk_by(increment: isize) -> Option<*mut u8> {
+    let old = unsafe { sbrk(increment) }.cast::<u8>();
+    // Failure is reported as `(void *)-1`.
+    (old.addr() != usize::MAX).then_some(old)
+}
+
+/// Returns `true` if the block ends exactly at the current break.
+#[inline]
+unsafe fn is_top(ptr: NonNull<u8>, size: usize) -> bool {
+    unsafe { brk_by(0) }.is_some_and(|top| ptr.as_ptr().wrapping_add(size) == top)
+}
+
+impl Deallocator for Sbrk {
+    /// Lowers the break if the block is the most recent allocation, and leaks it
+    /// otherwise.
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        LOCK.with(|_| unsafe {
+            if is_top(ptr, layout.size()) {
+                brk_by(-(layout.size() as isize));
+            }
+        })
+    }
+
+    /// Shrinking always succeeds in place. The break is lowered if the block is the most
+    /// recent allocation, and the tail is leaked otherwise.
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        LOCK.with(|_| unsafe {
+            if is_top(ptr, old_layout.size()) {
+                brk_by(-((old_layout.size() - new_layout.size()) as isize));
+            }
+        });
+        Ok(())
+    }
+}
+
+unsafe impl Allocator for Sbrk {
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let size = isize::try_from(layout.size()).map_err(|_| AllocError)?;
+
+        LOCK.with(|_| unsafe {
+            let top = brk_by(0).ok_or(AllocError)?;
+            let pad = top.align_offset(layout.align());
+            let total = size.checked_add(pad as isize).ok_or(AllocError)?;
+            let start = brk_by(total).ok_or(AllocError)?;
+
+            // Something else may have moved the break since it was read. The block can
+            // still be used as long as its alignment needs no more than the padding.
+            let offset = start.align_offset(layout.align());
+            if offset > pad {
+                if is_top(NonNull::new_unchecked(start), total as usize) {
+                    brk_by(-total);
+                }
+                return Err(AllocError);
+            }
+            Ok(NonNull::new_unchecked(start.add(offset)))
+        })
+    }
+
+    /// Fresh pages from the operating system are zeroed, but memory below a lowered
+    /// break can be handed out again with its old contents, so blocks are zeroed here.
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.allocate(layout)?;
+        unsafe { ptr::write_bytes(ptr.as_ptr(), 0, layout.size()) };
+        Ok(ptr)
+    }
+
+    /// Growing succeeds in place if the block is the most recent allocation and the
+    /// break can be raised.
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        if ptr.as_ptr().addr() & (new_layout.align() - 1) != 0 {
+            return Err(AllocError);
+        }
+        let extra =
+            isize::try_from(new_layout.size() - old_layout.size()).map_err(|_| AllocError)?;
+
+        LOCK.with(|_| unsafe {
+            let end = ptr.as_ptr().add(old_layout.size());
+            if !is_top(ptr, old_layout.size()) {
+                return Err(AllocError);
+            }
+            match brk_by(extra) {
+                Some(old) if old == end => Ok(()),
+                // Something else moved the break in between, so the new memory is not
+                // contiguous with the block.
+                Some(old) => {
+                    if is_top(NonNull::new_unchecked(old), extra as usize) {
+                        brk_by(-extra);
+                    }
+                    Err(AllocError)
+                }
+                None => Err(AllocError),
+            }
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use core::{
+    ffi::{c_int, c_void},
+    ptr::NonNull,
+    sync::atomic::{self, AtomicUsize, Ordering},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+extern "C" {
+    fn getpagesize() -> c_int;
+    fn mlock(addr: *const c_void, len: usize) -> c_int;
+    fn munlock(addr: *const c_void, len: usize) -> c_int;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MADV_DONTDUMP: c_int = 16;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MADV_DODUMP: c_int = 17;
+
+/// An allocator wrapper for memory holding keys and other secrets.
+///
+/// Every block is rounded up to whole pages, which are locked into memory with `mlock`
+/// so that they are never written to swap, and on Linux and Android excluded from core
+/// dumps. Blocks are zeroed before they are returned to the wrapped allocator.
+///
+/// Because blocks are padded to whole pages, this is only suitable for a small number of
+/// allocations, and the wrapped allocator must be able to serve page-aligned requests.
+/// Allocation fails if the pages cannot be locked, which usually means the process has
+/// reached its `RLIMIT_MEMLOCK` limit.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Secure<A> {
+    allocator: A,
+}
+
+impl<A> Secure<A> {
+    pub const fn new(allocator: A) -> Self {
+        Self { allocator }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+}
+
+#[inline]
+fn page_size() -> usize {
+    static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    match PAGE_SIZE.load(Ordering::Relaxed) {
+        0 => {
+            let size = unsafe { getpagesize() } as usize;
+            PAGE_SIZE.store(size, Ordering::Relaxed);
+            size
+        }
+        size => size,
+    }
+}
+
+/// The layout actually requested from the wrapped allocator: `layout` padded to whole
+/// pages.
+#[inline]
+fn outer(layout: NonZeroLayout) -> Result<NonZeroLayout, AllocError> {
+    NonZeroLayout::from_size_align(layout.size(), layout.align().max(page_size()))
+        .map(|layout| layout.pad_to_align())
+        .ok_or(AllocError)
+}
+
+/// Lock the pages of a freshly allocated block, and keep them out of core dumps.
+#[inline]
+unsafe fn protect(ptr: NonNull<u8>, layout: NonZeroLayout) -> Result<(), AllocError> {
+    if unsafe { mlock(ptr.as_ptr().cast(), layout.size()) } != 0 {
+        return Err(AllocError);
+    }
+    // Best effort: a block that cannot be excluded from core dumps is still usable.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe {
+        madvise(ptr.as_ptr().cast(), layout.size(), MADV_DONTDUMP)
+    };
+    Ok(())
+}
+
+/// Zero a block and undo [`protect`], before handing it back to the wrapped allocator.
+#[inline]
+unsafe fn release(ptr: NonNull<u8>, layout: NonZeroLayout) {
+    // Outer blocks are page aligned and a whole number of pages, so they can be cleared
+    // a word at a time. Volatile writes keep the zeroing from being optimized out as a
+    // dead store.
+    let words = ptr.cast::<usize>().as_ptr();
+    for i in 0..layout.size() / size_of::<usize>() {
+        unsafe { words.add(i).write_volatile(0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe {
+        madvise(ptr.as_ptr().cast(), layout.size(), MADV_DODUMP)
+    };
+    unsafe { munlock(ptr.as_ptr().cast(), layout.size()) };
+}
+
+impl<A> Secure<A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn protected(
+        &self,
+        layout: NonZeroLayout,
+        result: Result<NonNull<u8>, AllocError>,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = result?;
+        match unsafe { protect(ptr, layout) } {
+            Ok(()) => Ok(ptr),
+            Err(err) => {
+                unsafe { self.allocator.deallocate(ptr, layout) };
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<A> Deallocator for Secure<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        let layout = outer(layout).expect("invalid layout");
+        unsafe {
+            release(ptr, layout);
+            self.allocator.deallocate(ptr, layout);
+        }
+    }
+
+    /// Succeeds only if the block keeps the same number of pages. The bytes past the new
+    /// size are zeroed when the block is deallocated.
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        _ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        if outer(old_layout)? == outer(new_layout)? {
+            Ok(())
+        } else {
+            Err(AllocError)
+        }
+    }
+}
+
+unsafe impl<A> Allocator for Secure<A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let layout = outer(layout)?;
+        self.protected(layout, self.allocator.allocate(layout))
+    }
+
+    /// Reports the size of every page backing the block.
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        let outer = outer(layout)?;
+        let ptr = self.protected(outer, self.allocator.allocate(outer))?;
+        Ok((ptr, outer.size()))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let layout = outer(layout)?;
+        self.protected(layout, self.allocator.allocate_zeroed(layout))
+    }
+
+    /// Succeeds only if the block keeps the same number of pages. Otherwise, growing
+    /// moves the block to freshly locked pages and zeroes the old ones.
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        _ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        if outer(old_layout)? == outer(new_layout)? {
+            Ok(())
+        } else {
+            Err(AllocError)
+        }
+    }
+}
+
+impl<A> Owns for Secure<A>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        outer(layout).is_ok_and(|layout| self.allocator.owns(ptr, layout))
+    }
+}
+
+impl<A> Trim for Secure<A>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
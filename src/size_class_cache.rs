@@ -0,0 +1,292 @@
+use core::{
+    cell::{Cell, UnsafeCell},
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+/// The alignment of every cached block. Requests with a larger alignment bypass the
+/// cache.
+const CLASS_ALIGN: usize = 16;
+
+/// The size classes used by [`SizeClassCache::new`].
+pub const DEFAULT_SIZE_CLASSES: [usize; 8] = [16, 32, 64, 128, 256, 512, 1024, 2048];
+
+/// An allocator wrapper that caches freed small blocks by size class.
+///
+/// Requests are rounded up to the smallest of `C` size classes that fits them, and
+/// freed blocks are kept in a per-class magazine of up to `D` blocks, from which later
+/// requests of the same class are served without involving the wrapped allocator. When
+/// a magazine overflows, half of it is released to the wrapped allocator in a batch.
+/// Requests larger than the largest class, or aligned to more than 16 bytes, are
+/// forwarded directly.
+///
+/// This speeds up workloads that repeatedly allocate and free small objects, at the
+/// cost of holding on to up to `C * D` cached blocks. Cached blocks are released by
+/// [flush](Self::flush), by [trim](Trim::trim), and when the wrapper is dropped.
+#[derive(Debug)]
+pub struct SizeClassCache<A, const C: usize = 8, const D: usize = 32>
+where
+    A: Deallocator,
+{
+    allocator: A,
+    classes: [usize; C],
+    magazines: UnsafeCell<[[MaybeUninit<NonNull<u8>>; D]; C]>,
+    lens: [Cell<usize>; C],
+}
+
+impl<A> SizeClassCache<A>
+where
+    A: Deallocator,
+{
+    /// Create a cache with the [default size classes](DEFAULT_SIZE_CLASSES) and
+    /// magazine size. Other depths can be chosen through
+    /// [with_classes](Self::with_classes).
+    pub const fn new(allocator: A) -> Self {
+        Self::with_classes(allocator, DEFAULT_SIZE_CLASSES)
+    }
+}
+
+impl<A, const C: usize, const D: usize> SizeClassCache<A, C, D>
+where
+    A: Deallocator,
+{
+    /// Create a cache with the given size classes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the classes are not nonzero and strictly increasing, or if a class
+    /// cannot be rounded up to the cache's alignment.
+    pub const fn with_classes(allocator: A, classes: [usize; C]) -> Self {
+        let mut i = 0;
+        while i < C {
+            assert!(classes[i] != 0, "size classes must be nonzero");
+            assert!(
+                classes[i] <= isize::MAX as usize - (CLASS_ALIGN - 1),
+                "size class is too large"
+            );
+            assert!(
+                i == 0 || classes[i - 1] < classes[i],
+                "size classes must be strictly increasing"
+            );
+            i += 1;
+        }
+
+        Self {
+            allocator,
+            classes,
+            magazines: UnsafeCell::new([[MaybeUninit::uninit(); D]; C]),
+            lens: [const { Cell::new(0) }; C],
+        }
+    }
+
+    /// Returns the size classes of the cache.
+    pub fn classes(&self) -> &[usize; C] {
+        &self.classes
+    }
+
+    /// Returns the number of blocks currently cached.
+    pub fn cached(&self) -> usize {
+        self.lens.iter().map(Cell::get).sum()
+    }
+
+    /// Returns the total size of the blocks currently cached.
+    pub fn cached_bytes(&self) -> usize {
+        self.lens
+            .iter()
+            .zip(self.classes)
+            .map(|(len, size)| len.get() * size)
+            .sum()
+    }
+
+    /// Release every cached block to the wrapped allocator.
+    pub fn flush(&self) {
+        for class in 0..C {
+            self.release(class, self.lens[class].get());
+        }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.flush();
+        let this = ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.allocator) }
+    }
+
+    /// Returns the index of the size class that serves `layout`, if any.
+    #[inline]
+    fn class(&self, layout: NonZeroLayout) -> Option<usize> {
+        if D == 0 || layout.align() > CLASS_ALIGN {
+            return None;
+        }
+        self.classes.iter().position(|&size| size >= layout.size())
+    }
+
+    #[inline]
+    fn class_layout(&self, class: usize) -> NonZeroLayout {
+        // The class sizes were validated on construction.
+        NonZeroLayout::from_size_align(self.classes[class], CLASS_ALIGN).unwrap()
+    }
+
+    /// Release the `count` most recently cached blocks of a class.
+    fn release(&self, class: usize, count: usize) {
+        let layout = self.class_layout(class);
+        let len = self.lens[class].get();
+        for i in len - count..len {
+            unsafe {
+                let ptr = (*self.magazines.get())[class][i].assume_init();
+                self.allocator.deallocate(ptr, layout);
+            }
+        }
+        self.lens[class].set(len - count);
+    }
+
+    #[inline]
+    fn pop(&self, class: usize) -> Option<NonNull<u8>> {
+        let len = self.lens[class].get();
+        if len == 0 {
+            return None;
+        }
+        self.lens[class].set(len - 1);
+        Some(unsafe { (*self.magazines.get())[class][len - 1].assume_init() })
+    }
+}
+
+impl<A, const C: usize, const D: usize> Drop for SizeClassCache<A, C, D>
+where
+    A: Deallocator,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<A, const C: usize, const D: usize> Deallocator for SizeClassCache<A, C, D>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        let Some(class) = self.class(layout) else {
+            unsafe { self.allocator.deallocate(ptr, layout) };
+            return;
+        };
+
+        if self.lens[class].get() == D {
+            self.release(class, D.div_ceil(2));
+        }
+        let len = self.lens[class].get();
+        unsafe { (*self.magazines.get())[class][len].write(ptr) };
+        self.lens[class].set(len + 1);
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.try_shrink_at_least(ptr, old_layout, new_layout) }.map(|_| ())
+    }
+
+    #[inline]
+    unsafe fn try_shrink_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        match (self.class(old_layout), self.class(new_layout)) {
+            (Some(old), Some(new)) if old == new => Ok(self.classes[old]),
+            (None, None) => unsafe {
+                self.allocator
+                    .try_shrink_at_least(ptr, old_layout, new_layout)
+            },
+            _ => Err(AllocError),
+        }
+    }
+}
+
+unsafe impl<A, const C: usize, const D: usize> Allocator for SizeClassCache<A, C, D>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.allocate_at_least(layout).map(|(ptr, _)| ptr)
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        let Some(class) = self.class(layout) else {
+            return self.allocator.allocate_at_least(layout);
+        };
+
+        let ptr = match self.pop(class) {
+            Some(ptr) => ptr,
+            None => self.allocator.allocate(self.class_layout(class))?,
+        };
+        Ok((ptr, self.classes[class]))
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe { self.try_grow_at_least(ptr, old_layout, new_layout) }.map(|_| ())
+    }
+
+    #[inline]
+    unsafe fn try_grow_at_least(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<usize, AllocError> {
+        match (self.class(old_layout), self.class(new_layout)) {
+            (Some(old), Some(new)) if old == new => Ok(self.classes[old]),
+            (None, None) => unsafe {
+                self.allocator
+                    .try_grow_at_least(ptr, old_layout, new_layout)
+            },
+            _ => Err(AllocError),
+        }
+    }
+}
+
+impl<A, const C: usize, const D: usize> Owns for SizeClassCache<A, C, D>
+where
+    A: Deallocator + Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        self.allocator.owns(ptr, layout)
+    }
+}
+
+impl<A, const C: usize, const D: usize> Trim for SizeClassCache<A, C, D>
+where
+    A: Deallocator + Trim,
+{
+    /// Release all cached blocks before trimming the wrapped allocator.
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        let released = self.cached() > 0;
+        self.flush();
+        self.allocator.trim(pad) || released
+    }
+}
@@ -0,0 +1,106 @@
+use core::{cell::UnsafeCell, mem::MaybeUninit, ptr::NonNull};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::{FirstFit, FitStrategy, Heap};
+
+/// A heap over an inline buffer of `N` bytes, suitable for use in a `static`.
+///
+/// This is a [`Heap`] that owns its memory, and places blocks using the
+/// [fit strategy](FitStrategy) `S`. Since allocations point into the heap
+/// itself, the allocator traits are implemented for `&'static StaticHeap<N>`, which
+/// guarantees that the heap is never moved. To use it as the global allocator, combine
+/// it with [`WrapAsGlobal`](crate::WrapAsGlobal):
+///
+/// ```ignore
+/// static HEAP: StaticHeap<65536> = StaticHeap::new();
+///
+/// #[global_allocator]
+/// static GLOBAL: WrapAsGlobal<&StaticHeap<65536>> = WrapAsGlobal::new(&HEAP);
+/// ```
+#[derive(Debug)]
+pub struct StaticHeap<const N: usize, S = FirstFit> {
+    buffer: UnsafeCell<[MaybeUninit<u8>; N]>,
+    heap: Heap<S>,
+}
+
+unsafe impl<const N: usize, S> Sync for StaticHeap<N, S> where S: Send {}
+
+impl<const N: usize> StaticHeap<N> {
+    pub const fn new() -> Self {
+        Self::with_strategy(FirstFit)
+    }
+}
+
+impl<const N: usize, S> StaticHeap<N, S> {
+    pub const fn with_strategy(strategy: S) -> Self {
+        Self {
+            buffer: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            heap: Heap::with_strategy(strategy),
+        }
+    }
+
+    /// Returns the number of bytes currently allocated.
+    pub fn used(&self) -> usize {
+        self.heap.used()
+    }
+
+    /// Returns the number of bytes not currently allocated.
+    pub fn free(&self) -> usize {
+        N - self.used()
+    }
+
+    #[inline]
+    fn heap(&'static self) -> &'static Heap<S> {
+        self.heap.with_state(|state| {
+            if !state.is_initialized() {
+                // The buffer can only be handed to the heap once its address is fixed,
+                // which is why initialization is deferred until first use.
+                unsafe { state.init(self.buffer.get().cast(), N) };
+            }
+        });
+        &self.heap
+    }
+}
+
+impl<const N: usize, S> Default for StaticHeap<N, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_strategy(S::default())
+    }
+}
+
+impl<const N: usize, S> Deallocator for &'static StaticHeap<N, S>
+where
+    S: FitStrategy,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe { self.heap.deallocate(ptr, layout) }
+    }
+}
+
+unsafe impl<const N: usize, S> Allocator for &'static StaticHeap<N, S>
+where
+    S: FitStrategy,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        self.heap().allocate(layout)
+    }
+}
+
+impl<const N: usize, S> Owns for StaticHeap<N, S> {
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        let start = self.buffer.get().cast::<u8>().addr();
+        let addr = ptr.as_ptr().addr();
+
+        start <= addr
+            && addr
+                .checked_add(layout.size())
+                .is_some_and(|end| end <= start + N)
+    }
+}
@@ -0,0 +1,247 @@
+use core::{
+    fmt::{self, Display},
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+use crate::Watermarks;
+
+/// Heap usage statistics collected by a [`WrapAsGlobal`](crate::WrapAsGlobal) or
+/// [`Counted`](crate::Counted) allocator.
+///
+/// Statistics can be arranged into a tree mirroring a composed allocator stack, see
+/// [attach](GlobalStats::attach). When every child allocator obtains its memory through
+/// its parent, each child's live bytes are a subset of its parent's, and
+/// [exclusive_live_bytes](GlobalStats::exclusive_live_bytes) attributes the remainder to
+/// the parent itself.
+#[derive(Debug, Default)]
+pub struct GlobalStats {
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    allocations: AtomicUsize,
+    label: Option<&'static str>,
+    parent: AtomicPtr<GlobalStats>,
+    first_child: AtomicPtr<GlobalStats>,
+    next_sibling: AtomicPtr<GlobalStats>,
+    watermarks: Option<Watermarks>,
+}
+
+impl GlobalStats {
+    pub const fn new() -> Self {
+        Self {
+            live_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            label: None,
+            parent: AtomicPtr::new(ptr::null_mut()),
+            first_child: AtomicPtr::new(ptr::null_mut()),
+            next_sibling: AtomicPtr::new(ptr::null_mut()),
+            watermarks: None,
+        }
+    }
+
+    /// Create statistics with a label, which identifies them in a [tree](Self::tree)
+    /// report.
+    pub const fn with_label(label: &'static str) -> Self {
+        let mut stats = Self::new();
+        stats.label = Some(label);
+        stats
+    }
+
+    /// Watch the live byte count, calling back when it crosses the given watermarks.
+    pub const fn with_watermarks(mut self, watermarks: Watermarks) -> Self {
+        self.watermarks = Some(watermarks);
+        self
+    }
+
+    pub fn watermarks(&self) -> Option<&Watermarks> {
+        self.watermarks.as_ref()
+    }
+
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Returns the number of bytes currently allocated.
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the largest number of bytes that have been allocated at once.
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of successful allocations. Reallocations are not
+    /// counted.
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    /// Returns a copy of all statistics. Each figure is read independently, so they
+    /// may be slightly inconsistent with one another under concurrent use.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            live_bytes: self.live_bytes(),
+            peak_bytes: self.peak_bytes(),
+            allocations: self.allocations(),
+        }
+    }
+
+    /// Make these statistics a child of `parent`.
+    ///
+    /// # Panics
+    /// Panics if these statistics have already been attached to a parent, or if
+    /// `parent` is these statistics or one of their descendants, which would make the
+    /// tree a cycle.
+    pub fn attach(&'static self, parent: &'static GlobalStats) {
+        let this = ptr::from_ref(self).cast_mut();
+        let already = self
+            .parent
+            .compare_exchange(
+                ptr::null_mut(),
+                ptr::from_ref(parent).cast_mut(),
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            )
+            .is_err();
+        assert!(!already, "statistics are already attached to a parent");
+
+        // The parent is recorded first, so that of two racing attaches that would form
+        // a cycle, at least one sees the other's link here.
+        let mut ancestor = Some(parent);
+        while let Some(stats) = ancestor {
+            if ptr::eq(stats, self) {
+                self.parent.store(ptr::null_mut(), Ordering::SeqCst);
+                panic!("attaching statistics to themselves or a descendant would create a cycle");
+            }
+            ancestor = unsafe { stats.parent.load(Ordering::SeqCst).as_ref() };
+        }
+
+        let mut head = parent.first_child.load(Ordering::Acquire);
+        loop {
+            self.next_sibling.store(head, Ordering::Relaxed);
+            match parent.first_child.compare_exchange_weak(
+                head,
+                this,
+                Ordering::Release,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Returns the statistics attached to these, most recently attached first.
+    pub fn children(&self) -> StatsChildren<'_> {
+        StatsChildren {
+            next: unsafe { self.first_child.load(Ordering::Acquire).as_ref() },
+        }
+    }
+
+    /// Returns the live bytes not accounted for by any child: bytes allocated at this
+    /// level directly, or held as slack by the child allocators.
+    pub fn exclusive_live_bytes(&self) -> usize {
+        let children: usize = self.children().map(GlobalStats::live_bytes).sum();
+        self.live_bytes().saturating_sub(children)
+    }
+
+    /// Returns a value that displays these statistics and all of their descendants as an
+    /// indented tree.
+    pub fn tree(&self) -> StatsTree<'_> {
+        StatsTree { root: self }
+    }
+
+    #[inline]
+    pub(crate) fn record_alloc(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.add_live(size);
+    }
+
+    #[inline]
+    pub(crate) fn record_dealloc(&self, size: usize) {
+        let live = self
+            .live_bytes
+            .fetch_sub(size, Ordering::Relaxed)
+            .wrapping_sub(size);
+        if let Some(watermarks) = &self.watermarks {
+            watermarks.update(live);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record_realloc(&self, old_size: usize, new_size: usize) {
+        if new_size > old_size {
+            self.add_live(new_size - old_size);
+        } else {
+            self.record_dealloc(old_size - new_size);
+        }
+    }
+
+    #[inline]
+    fn add_live(&self, size: usize) {
+        let live = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live, Ordering::Relaxed);
+        if let Some(watermarks) = &self.watermarks {
+            watermarks.update(live);
+        }
+    }
+}
+
+/// A point-in-time copy of [`GlobalStats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    pub live_bytes: usize,
+    pub peak_bytes: usize,
+    pub allocations: usize,
+}
+
+/// An iterator over the children of a [`GlobalStats`].
+#[derive(Debug, Clone)]
+pub struct StatsChildren<'a> {
+    next: Option<&'a GlobalStats>,
+}
+
+impl<'a> Iterator for StatsChildren<'a> {
+    type Item = &'a GlobalStats;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        // Children are only ever attached with a 'static lifetime and never detached.
+        self.next = unsafe { current.next_sibling.load(Ordering::Acquire).as_ref() };
+        Some(current)
+    }
+}
+
+/// Displays a tree of [`GlobalStats`], see [tree](GlobalStats::tree).
+#[derive(Debug, Clone, Copy)]
+pub struct StatsTree<'a> {
+    root: &'a GlobalStats,
+}
+
+impl StatsTree<'_> {
+    fn fmt_node(f: &mut fmt::Formatter<'_>, stats: &GlobalStats, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{}: {} bytes live ({} exclusive), {} peak, {} allocations",
+            "",
+            stats.label().unwrap_or("<unlabeled>"),
+            stats.live_bytes(),
+            stats.exclusive_live_bytes(),
+            stats.peak_bytes(),
+            stats.allocations(),
+            indent = depth * 2,
+        )?;
+        for child in stats.children() {
+            Self::fmt_node(f, child, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for StatsTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::fmt_node(f, self.root, 0)
+    }
+}
@@ -0,0 +1,191 @@
+//! Implementations of the unstable `core::alloc::Allocator` trait, so that divvy allocators
+//! can be passed directly to the standard collections' `new_in` constructors.
+//!
+//! The standard trait permits zero-sized layouts, which divvy's does not. Those are
+//! served with dangling pointers and never reach the underlying allocator.
+
+use core::{
+    alloc::{self, Layout},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{Allocator, NonZeroLayout};
+
+use crate::FixedSlice;
+#[cfg(feature = "alloc")]
+use crate::Global;
+
+#[inline]
+fn dangling(layout: Layout) -> NonNull<[u8]> {
+    let ptr = unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) };
+    NonNull::slice_from_raw_parts(ptr, 0)
+}
+
+#[inline]
+fn allocate_with(
+    layout: Layout,
+    f: impl FnOnce(NonZeroLayout) -> Result<NonNull<u8>, divvy_core::AllocError>,
+) -> Result<NonNull<[u8]>, alloc::AllocError> {
+    match NonZeroLayout::new(layout) {
+        Some(layout) => {
+            let ptr = f(layout).map_err(|_| alloc::AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+        None => Ok(dangling(layout)),
+    }
+}
+
+#[inline]
+fn allocate<A: Allocator>(a: &A, layout: Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+    match NonZeroLayout::new(layout) {
+        Some(layout) => {
+            let (ptr, size) = a.allocate_at_least(layout).map_err(|_| alloc::AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, size))
+        }
+        None => Ok(dangling(layout)),
+    }
+}
+
+#[inline]
+unsafe fn deallocate<A: Allocator>(a: &A, ptr: NonNull<u8>, layout: Layout) {
+    if let Some(layout) = NonZeroLayout::new(layout) {
+        unsafe { a.deallocate(ptr, layout) };
+    }
+}
+
+#[inline]
+unsafe fn grow<A: Allocator>(
+    a: &A,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+    zeroed: bool,
+) -> Result<NonNull<[u8]>, alloc::AllocError> {
+    let Some(old_layout) = NonZeroLayout::new(old_layout) else {
+        return if zeroed {
+            allocate_with(new_layout, |l| a.allocate_zeroed(l))
+        } else {
+            allocate_with(new_layout, |l| a.allocate(l))
+        };
+    };
+    // The new layout is at least as large as the old one, so this never fails.
+    let new_layout = NonZeroLayout::new(new_layout).ok_or(alloc::AllocError)?;
+    if new_layout.align() > old_layout.align() {
+        return unsafe { realign(a, ptr, old_layout, new_layout, zeroed) };
+    }
+    let ptr = unsafe {
+        if zeroed {
+            a.grow_zeroed(ptr, old_layout, new_layout)
+        } else {
+            a.grow(ptr, old_layout, new_layout)
+        }
+    }
+    .map_err(|_| alloc::AllocError)?;
+    Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+}
+
+#[inline]
+unsafe fn shrink<A: Allocator>(
+    a: &A,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+) -> Result<NonNull<[u8]>, alloc::AllocError> {
+    let Some(old_layout) = NonZeroLayout::new(old_layout) else {
+        return Ok(dangling(new_layout));
+    };
+    let Some(new_layout) = NonZeroLayout::new(new_layout) else {
+        unsafe { a.deallocate(ptr, old_layout) };
+        return Ok(dangling(new_layout));
+    };
+    if new_layout.align() > old_layout.align() {
+        return unsafe { realign(a, ptr, old_layout, new_layout, false) };
+    }
+    let ptr = unsafe { a.shrink(ptr, old_layout, new_layout) }.map_err(|_| alloc::AllocError)?;
+    Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+}
+
+/// Move a block into a new one with a larger alignment.
+///
+/// The standard trait allows `grow` and `shrink` to increase the alignment, but divvy's
+/// resizing methods may keep the block in place, so this is done by hand.
+#[cold]
+unsafe fn realign<A: Allocator>(
+    a: &A,
+    ptr: NonNull<u8>,
+    old_layout: NonZeroLayout,
+    new_layout: NonZeroLayout,
+    zeroed: bool,
+) -> Result<NonNull<[u8]>, alloc::AllocError> {
+    let new_ptr = if zeroed {
+        a.allocate_zeroed(new_layout)
+    } else {
+        a.allocate(new_layout)
+    }
+    .map_err(|_| alloc::AllocError)?;
+
+    unsafe {
+        let size = old_layout.size().min(new_layout.size());
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), size);
+        a.deallocate(ptr, old_layout);
+    }
+    Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size()))
+}
+
+macro_rules! impl_std_allocator {
+    ($($(#[$attr:meta])* [$($generics:tt)*] $ty:ty),* $(,)?) => {$(
+        $(#[$attr])*
+        unsafe impl<$($generics)*> alloc::Allocator for $ty {
+            #[inline]
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+                allocate(self, layout)
+            }
+
+            #[inline]
+            fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+                allocate_with(layout, |l| Allocator::allocate_zeroed(self, l))
+            }
+
+            #[inline]
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                unsafe { deallocate(self, ptr, layout) }
+            }
+
+            #[inline]
+            unsafe fn grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+                unsafe { grow(self, ptr, old_layout, new_layout, false) }
+            }
+
+            #[inline]
+            unsafe fn grow_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+                unsafe { grow(self, ptr, old_layout, new_layout, true) }
+            }
+
+            #[inline]
+            unsafe fn shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+                unsafe { shrink(self, ptr, old_layout, new_layout) }
+            }
+        }
+    )*};
+}
+
+impl_std_allocator! {
+    ['a] FixedSlice<'a>,
+    #[cfg(feature = "alloc")]
+    [] Global,
+}
@@ -0,0 +1,335 @@
+use core::{
+    cmp, mem,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use divvy_core::{AllocError, Allocator, Deallocator, NonZeroLayout, Owns};
+
+use crate::Trim;
+
+static NEXT_TAG: AtomicUsize = AtomicUsize::new(1);
+
+/// A debugging wrapper that stamps every block with the identity of the allocator instance
+/// that produced it.
+///
+/// The tag is stored in a header word immediately before each returned block, rather than
+/// in the unused high bits of the pointer itself: untagged high bits are required for a
+/// pointer to be dereferenceable on most targets. Deallocating, growing, or shrinking a
+/// block checks the tag and panics on a mismatch, which catches blocks routed to the wrong
+/// allocator in composed stacks.
+///
+/// The check reads the word before the pointer, so a foreign block is only reliably
+/// detected if that word is readable memory. Clones share their tag and can free each
+/// other's blocks.
+#[derive(Debug, Clone)]
+pub struct Tagged<A> {
+    allocator: A,
+    tag: usize,
+}
+
+impl<A> Tagged<A> {
+    /// Wrap `allocator`, assigning it a tag not used by any other `Tagged::new` instance.
+    pub fn new(allocator: A) -> Self {
+        let tag = NEXT_TAG.fetch_add(1, Ordering::Relaxed);
+        Self { allocator, tag }
+    }
+
+    /// Wrap `allocator` with an explicitly chosen tag.
+    pub const fn with_tag(allocator: A, tag: usize) -> Self {
+        Self { allocator, tag }
+    }
+
+    pub fn tag(&self) -> usize {
+        self.tag
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+
+    /// Returns `true` if a live block carries this wrapper's tag.
+    ///
+    /// Unlike [owns](Owns::owns), which only checks the range of the wrapped allocator,
+    /// this tells apart blocks from different wrappers around the same allocator.
+    ///
+    /// # Safety
+    ///
+    /// The block must be currently allocated through a `Tagged` wrapper.
+    #[inline]
+    pub unsafe fn has_tag(&self, ptr: NonNull<u8>) -> bool {
+        unsafe { ptr::read(Self::tag_slot(ptr)) == self.tag }
+    }
+
+    /// The distance from the start of the underlying block to the user's block. The tag
+    /// occupies the word just before the user's block.
+    #[inline]
+    fn offset(layout: NonZeroLayout) -> usize {
+        cmp::max(layout.align(), mem::size_of::<usize>())
+    }
+
+    #[inline]
+    fn outer(layout: NonZeroLayout) -> Result<NonZeroLayout, AllocError> {
+        let size = Self::offset(layout)
+            .checked_add(layout.size())
+            .ok_or(AllocError)?;
+        let align = cmp::max(layout.align(), mem::align_of::<usize>());
+        NonZeroLayout::from_size_align(size, align).ok_or(AllocError)
+    }
+
+    /// Compute the outer layout of a block that was previously allocated through this
+    /// wrapper, which is therefore known to be valid.
+    #[inline]
+    unsafe fn outer_existing(layout: NonZeroLayout) -> NonZeroLayout {
+        match Self::outer(layout) {
+            Ok(layout) => layout,
+            Err(_) => unsafe { core::hint::unreachable_unchecked() },
+        }
+    }
+
+    #[inline]
+    unsafe fn tag_slot(ptr: NonNull<u8>) -> *mut usize {
+        unsafe { ptr.as_ptr().sub(mem::size_of::<usize>()).cast() }
+    }
+
+    /// Write the tag into a freshly allocated outer block and return the user's block.
+    #[inline]
+    unsafe fn stamp(&self, outer: NonNull<u8>, layout: NonZeroLayout) -> NonNull<u8> {
+        unsafe {
+            let ptr = outer.add(Self::offset(layout));
+            ptr::write(Self::tag_slot(ptr), self.tag);
+            ptr
+        }
+    }
+
+    /// Check the tag of a user's block and return the outer block.
+    #[inline]
+    #[track_caller]
+    unsafe fn check(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> NonNull<u8> {
+        let found = unsafe { ptr::read(Self::tag_slot(ptr)) };
+        if found != self.tag {
+            panic!(
+                "block {ptr:p} was not allocated by this allocator (expected tag {}, found {})",
+                self.tag, found
+            );
+        }
+        unsafe { ptr.sub(Self::offset(layout)) }
+    }
+}
+
+impl<A> Tagged<A>
+where
+    A: Allocator,
+{
+    /// Move a block to a new layout with a different alignment, and therefore a different
+    /// header offset, by allocating a fresh block and copying.
+    #[inline]
+    unsafe fn relocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+        zeroed: bool,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new = if zeroed {
+            self.allocate_zeroed(new_layout)?
+        } else {
+            self.allocate(new_layout)?
+        };
+        unsafe {
+            let count = cmp::min(old_layout.size(), new_layout.size());
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new.as_ptr(), count);
+            self.deallocate(ptr, old_layout);
+        }
+        Ok(new)
+    }
+}
+
+impl<A> Deallocator for Tagged<A>
+where
+    A: Deallocator,
+{
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: NonZeroLayout) {
+        unsafe {
+            let outer = self.check(ptr, layout);
+            self.allocator
+                .deallocate(outer, Self::outer_existing(layout))
+        }
+    }
+
+    #[inline]
+    unsafe fn try_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            let outer = self.check(ptr, old_layout);
+            if Self::offset(old_layout) != Self::offset(new_layout) {
+                return Err(AllocError);
+            }
+            let old_outer = Self::outer_existing(old_layout);
+            let new_outer = Self::outer(new_layout)?;
+            self.allocator.try_shrink(outer, old_outer, new_outer)
+        }
+    }
+}
+
+unsafe impl<A> Allocator for Tagged<A>
+where
+    A: Allocator,
+{
+    #[inline]
+    fn allocate(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let outer = self.allocator.allocate(Self::outer(layout)?)?;
+        Ok(unsafe { self.stamp(outer, layout) })
+    }
+
+    #[inline]
+    fn allocate_at_least(&self, layout: NonZeroLayout) -> Result<(NonNull<u8>, usize), AllocError> {
+        let (outer, size) = self.allocator.allocate_at_least(Self::outer(layout)?)?;
+        let ptr = unsafe { self.stamp(outer, layout) };
+        Ok((ptr, size - Self::offset(layout)))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: NonZeroLayout) -> Result<NonNull<u8>, AllocError> {
+        let outer = self.allocator.allocate_zeroed(Self::outer(layout)?)?;
+        Ok(unsafe { self.stamp(outer, layout) })
+    }
+
+    #[inline]
+    fn allocate_filled(&self, layout: NonZeroLayout, byte: u8) -> Result<NonNull<u8>, AllocError> {
+        let outer = self.allocator.allocate_filled(Self::outer(layout)?, byte)?;
+        Ok(unsafe { self.stamp(outer, layout) })
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            let outer = self.check(ptr, old_layout);
+            if Self::offset(old_layout) != Self::offset(new_layout) {
+                return self.relocate(ptr, old_layout, new_layout, false);
+            }
+            let old_outer = Self::outer_existing(old_layout);
+            let new_outer = Self::outer(new_layout)?;
+            let outer = self.allocator.grow(outer, old_outer, new_outer)?;
+            Ok(outer.add(Self::offset(new_layout)))
+        }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            let outer = self.check(ptr, old_layout);
+            if Self::offset(old_layout) != Self::offset(new_layout) {
+                return self.relocate(ptr, old_layout, new_layout, true);
+            }
+            let old_outer = Self::outer_existing(old_layout);
+            let new_outer = Self::outer(new_layout)?;
+            let outer = self.allocator.grow_zeroed(outer, old_outer, new_outer)?;
+            Ok(outer.add(Self::offset(new_layout)))
+        }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            let outer = self.check(ptr, old_layout);
+            if Self::offset(old_layout) != Self::offset(new_layout) {
+                return self.relocate(ptr, old_layout, new_layout, false);
+            }
+            let old_outer = Self::outer_existing(old_layout);
+            let new_outer = Self::outer(new_layout)?;
+            let outer = self.allocator.shrink(outer, old_outer, new_outer)?;
+            Ok(outer.add(Self::offset(new_layout)))
+        }
+    }
+
+    #[inline]
+    unsafe fn try_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            let outer = self.check(ptr, old_layout);
+            if Self::offset(old_layout) != Self::offset(new_layout) {
+                return Err(AllocError);
+            }
+            let old_outer = Self::outer_existing(old_layout);
+            let new_outer = Self::outer(new_layout)?;
+            self.allocator.try_grow(outer, old_outer, new_outer)
+        }
+    }
+
+    #[inline]
+    unsafe fn try_grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: NonZeroLayout,
+        new_layout: NonZeroLayout,
+    ) -> Result<(), AllocError> {
+        unsafe {
+            let outer = self.check(ptr, old_layout);
+            if Self::offset(old_layout) != Self::offset(new_layout) {
+                return Err(AllocError);
+            }
+            let old_outer = Self::outer_existing(old_layout);
+            let new_outer = Self::outer(new_layout)?;
+            self.allocator.try_grow_zeroed(outer, old_outer, new_outer)
+        }
+    }
+}
+
+impl<A> Owns for Tagged<A>
+where
+    A: Owns,
+{
+    #[inline]
+    fn owns(&self, ptr: NonNull<u8>, layout: NonZeroLayout) -> bool {
+        let Ok(outer_layout) = Self::outer(layout) else {
+            return false;
+        };
+        let Some(outer) = NonNull::new(ptr.as_ptr().wrapping_sub(Self::offset(layout))) else {
+            return false;
+        };
+        self.allocator.owns(outer, outer_layout)
+    }
+}
+
+impl<A> Trim for Tagged<A>
+where
+    A: Trim,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        self.allocator.trim(pad)
+    }
+}
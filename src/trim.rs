@@ -0,0 +1,61 @@
+/// An allocator that can release unused memory back to its source on demand.
+pub trait Trim {
+    /// Release as much unused memory as possible, retaining at least `pad` bytes of
+    /// free memory for future allocations.
+    ///
+    /// Returns `true` if any memory was released.
+    fn trim(&self, pad: usize) -> bool;
+}
+
+impl<A> Trim for &A
+where
+    A: Trim + ?Sized,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        (**self).trim(pad)
+    }
+}
+
+impl<A> Trim for &mut A
+where
+    A: Trim + ?Sized,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        (**self).trim(pad)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A> Trim for alloc::boxed::Box<A>
+where
+    A: Trim + ?Sized,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        (**self).trim(pad)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A> Trim for alloc::rc::Rc<A>
+where
+    A: Trim + ?Sized,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        (**self).trim(pad)
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<A> Trim for alloc::sync::Arc<A>
+where
+    A: Trim + ?Sized,
+{
+    #[inline]
+    fn trim(&self, pad: usize) -> bool {
+        (**self).trim(pad)
+    }
+}
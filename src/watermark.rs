@@ -0,0 +1,73 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Which threshold of a [`Watermarks`] was crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watermark {
+    /// Usage rose to or above the high watermark.
+    High,
+    /// Usage fell to or below the low watermark, after having crossed the high one.
+    Low,
+}
+
+/// A pair of usage thresholds with a callback, for reacting to memory pressure before
+/// allocations start failing.
+///
+/// The callback fires once with [`Watermark::High`] when usage rises to the high
+/// watermark, and once with [`Watermark::Low`] when it later falls back to the low
+/// watermark. Keeping the low watermark below the high one avoids repeated callbacks when
+/// usage hovers around a single threshold.
+///
+/// The callback receives the usage that triggered it, and runs inside the allocator, so
+/// it must not allocate from the allocator being watched.
+#[derive(Debug)]
+pub struct Watermarks {
+    high: usize,
+    low: usize,
+    hook: fn(Watermark, usize),
+    above: AtomicBool,
+}
+
+impl Watermarks {
+    pub const fn new(high: usize, low: usize, hook: fn(Watermark, usize)) -> Self {
+        Self {
+            high,
+            low,
+            hook,
+            above: AtomicBool::new(false),
+        }
+    }
+
+    pub fn high(&self) -> usize {
+        self.high
+    }
+
+    pub fn low(&self) -> usize {
+        self.low
+    }
+
+    /// Returns `true` if usage has crossed the high watermark and not yet fallen back
+    /// to the low one.
+    pub fn is_high(&self) -> bool {
+        self.above.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn update(&self, usage: usize) {
+        if usage >= self.high {
+            if self
+                .above
+                .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
+                (self.hook)(Watermark::High, usage);
+            }
+        } else if usage <= self.low
+            && self
+                .above
+                .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            (self.hook)(Watermark::Low, usage);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ptr::{self, NonNull},
+};
+
+use divvy_core::{AllocError, Allocator, NonZeroLayout};
+
+use crate::{GlobalStats, Watermarks};
+
+/// The action taken by [`WrapAsGlobal`] after its out-of-memory hook has been called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OomAction {
+    /// Attempt the failed operation again.
+    Retry,
+    /// Report the failure to the caller by returning a null pointer.
+    Fail,
+    /// Call `handle_alloc_error` with the failed layout. Without the `alloc` feature,
+    /// this panics instead.
+    Abort,
+}
+
+/// Adapts an [`Allocator`] to the [`GlobalAlloc`] interface, so that it can be used as
+/// the `#[global_allocator]`.
+///
+/// Usage statistics are optionally collected, see
+/// [with_stats](WrapAsGlobal::with_stats), and a hook may be installed to handle
+/// allocation failures, see [with_oom_hook](WrapAsGlobal::with_oom_hook).
+#[derive(Debug, Default)]
+pub struct WrapAsGlobal<A> {
+    allocator: A,
+    stats: Option<GlobalStats>,
+    oom_hook: Option<fn(Layout) -> OomAction>,
+}
+
+impl<A> WrapAsGlobal<A> {
+    pub const fn new(allocator: A) -> Self {
+        Self {
+            allocator,
+            stats: None,
+            oom_hook: None,
+        }
+    }
+
+    /// Create a new adapter that keeps track of heap usage. The statistics can be
+    /// queried through [stats](WrapAsGlobal::stats).
+    pub const fn with_stats(allocator: A) -> Self {
+        Self {
+            allocator,
+            stats: Some(GlobalStats::new()),
+            oom_hook: None,
+        }
+    }
+
+    /// Install a hook that is called whenever the wrapped allocator fails, before a
+    /// null pointer is returned. The hook receives the requested layout and decides
+    /// whether the operation is retried, reported as a failure, or aborts.
+    ///
+    /// The hook runs inside the global allocator, so it must not allocate.
+    pub const fn with_oom_hook(mut self, hook: fn(Layout) -> OomAction) -> Self {
+        self.oom_hook = Some(hook);
+        self
+    }
+
+    /// Collect usage statistics, if not already enabled, and call `watermarks` back when
+    /// the live byte count crosses its thresholds.
+    pub const fn with_watermarks(mut self, watermarks: Watermarks) -> Self {
+        let stats = match self.stats.take() {
+            Some(stats) => stats,
+            None => GlobalStats::new(),
+        };
+        self.stats = Some(stats.with_watermarks(watermarks));
+        self
+    }
+
+    /// Returns the collected usage statistics, or `None` if this adapter was not
+    /// created with [with_stats](WrapAsGlobal::with_stats).
+    pub fn stats(&self) -> Option<&GlobalStats> {
+        self.stats.as_ref()
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.allocator
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.allocator
+    }
+
+    pub fn into_inner(self) -> A {
+        self.allocator
+    }
+
+    #[inline]
+    fn allocate_with(
+        &self,
+        layout: Layout,
+        mut f: impl FnMut(&A) -> Result<NonNull<u8>, AllocError>,
+    ) -> *mut u8 {
+        loop {
+            match f(&self.allocator) {
+                Ok(ptr) => return ptr.as_ptr(),
+                Err(AllocError) => {
+                    let Some(hook) = self.oom_hook else {
+                        return ptr::null_mut();
+                    };
+                    match hook(layout) {
+                        OomAction::Retry => continue,
+                        OomAction::Fail => return ptr::null_mut(),
+                        OomAction::Abort => abort(layout),
+                    }
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn record_alloc(&self, result: *mut u8, size: usize) -> *mut u8 {
+        if let Some(stats) = &self.stats {
+            if !result.is_null() {
+                stats.record_alloc(size);
+            }
+        }
+        result
+    }
+
+    #[inline]
+    fn record_dealloc(&self, size: usize) {
+        if let Some(stats) = &self.stats {
+            stats.record_dealloc(size);
+        }
+    }
+
+    #[inline]
+    fn record_realloc(&self, result: *mut u8, old_size: usize, new_size: usize) -> *mut u8 {
+        if let Some(stats) = &self.stats {
+            if !result.is_null() {
+                stats.record_realloc(old_size, new_size);
+            }
+        }
+        result
+    }
+}
+
+unsafe impl<A> GlobalAlloc for WrapAsGlobal<A>
+where
+    A: Allocator,
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if let Some(layout) = NonZeroLayout::new(layout) {
+            let result = self.allocate_with(layout.get(), |a| a.allocate(layout));
+            self.record_alloc(result, layout.size())
+        } else {
+            ptr::without_provenance_mut(layout.align())
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let Some(layout) = NonZeroLayout::new(layout) else {
+            return;
+        };
+        let Some(ptr) = NonNull::new(ptr) else { return };
+        unsafe { self.allocator.deallocate(ptr, layout) };
+        self.record_dealloc(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if let Some(layout) = NonZeroLayout::new(layout) {
+            let result = self.allocate_with(layout.get(), |a| a.allocate_zeroed(layout));
+            self.record_alloc(result, layout.size())
+        } else {
+            ptr::without_provenance_mut(layout.align())
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let old_layout = NonZeroLayout::new(layout);
+        let Ok(new_layout) = Layout::from_size_align(new_size, layout.align()) else {
+            return ptr::null_mut();
+        };
+
+        let new_layout = NonZeroLayout::new(new_layout);
+
+        match (old_layout, new_layout) {
+            (None, None) => ptr,
+            (None, Some(new_layout)) => {
+                let result = self.allocate_with(new_layout.get(), |a| a.allocate(new_layout));
+                self.record_alloc(result, new_layout.size())
+            }
+            (Some(old_layout), None) => {
+                if let Some(ptr) = NonNull::new(ptr) {
+                    self.allocator.deallocate(ptr, old_layout);
+                    self.record_dealloc(old_layout.size());
+                }
+                ptr::without_provenance_mut(layout.align())
+            }
+            (Some(old_layout), Some(new_layout)) => {
+                let ptr = unsafe { NonNull::new_unchecked(ptr) };
+
+                let result = self.allocate_with(new_layout.get(), |a| unsafe {
+                    a.reallocate(ptr, old_layout, new_layout)
+                });
+                self.record_realloc(result, old_layout.size(), new_layout.size())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn abort(layout: Layout) -> ! {
+    alloc::alloc::handle_alloc_error(layout)
+}
+
+#[cfg(not(feature = "alloc"))]
+fn abort(layout: Layout) -> ! {
+    panic!("memory allocation of {} bytes failed", layout.size())
+}